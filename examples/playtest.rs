@@ -0,0 +1,62 @@
+//! Rides a handful of seeds with the autopilot
+//! and prints how each run went.
+//!
+//! `cargo run --example playtest -- [seeds]
+//! [seconds] [first seed]`
+
+use std::time::Duration;
+
+use landing::{
+    headless::playtest, scoring::LandingQuality,
+    terrain_chunking::TerrainSeed, tuning::GameTuning,
+};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let seeds: u32 = args
+        .next()
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(5);
+    let seconds: f32 = args
+        .next()
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(60.);
+    let first_seed: u32 = args
+        .next()
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(TerrainSeed::default().0);
+
+    let tuning: GameTuning =
+        std::fs::read_to_string("assets/game.tuning.ron")
+            .ok()
+            .and_then(|tuning| ron::from_str(&tuning).ok())
+            .unwrap_or_default();
+
+    println!(
+        "seed\tseconds\tdistance\ttop speed\thits\tperfect\tgood\tok\tmeh\tgame over\tfell through"
+    );
+    for seed in first_seed..first_seed + seeds {
+        let report = playtest(
+            seed,
+            seconds,
+            tuning.clone(),
+            Duration::from_secs_f64(1. / 64.),
+        );
+        let landings = LandingQuality::ALL
+            .map(|quality| report.landings.count(quality));
+        println!(
+            "{}\t{:.1}\t{:.0}\t{:.1}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            report.seed,
+            report.seconds,
+            report.distance,
+            report.top_speed,
+            report.hits,
+            landings[0],
+            landings[1],
+            landings[2],
+            landings[3],
+            report.game_over,
+            report.fell_through,
+        );
+    }
+}
@@ -0,0 +1,126 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::*;
+use bevy::{color::palettes::tailwind::*, prelude::*};
+
+use crate::{
+    AppState, GameLayer,
+    movement::GroundState,
+    playing::{Player, PlayerFollowCamera, rider_body},
+    terrain_chunking::{LandChunkNoise, terrain_height},
+    tuning::GameTuning,
+};
+
+/// Seconds for the camera to circle the attract
+/// rider once.
+const ORBIT_PERIOD: f32 = 40.;
+
+pub struct AttractPlugin;
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            spawn_attract_rider,
+        )
+        .add_systems(
+            FixedUpdate,
+            restart_stalled_attract_run
+                .run_if(in_state(AppState::MainMenu)),
+        )
+        .add_systems(
+            Update,
+            orbit_attract_camera
+                .run_if(in_state(AppState::MainMenu)),
+        );
+    }
+}
+
+/// The rider the autopilot takes down the hill
+/// behind the main menu. Gone as soon as the menu
+/// is, along with the terrain it streamed in.
+#[derive(Component)]
+struct AttractRider;
+
+fn attract_start(noise: &LandChunkNoise) -> Vec3 {
+    Vec3::new(
+        0.,
+        terrain_height(noise, 0., 0.) + 1.,
+        0.,
+    )
+}
+
+fn spawn_attract_rider(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    noise: Res<LandChunkNoise>,
+    tuning: Res<GameTuning>,
+) {
+    commands.spawn((
+        Name::new("Attract Rider"),
+        AttractRider,
+        // so the autopilot picks it up
+        Player,
+        StateScoped(AppState::MainMenu),
+        TransformInterpolation,
+        Mesh3d(meshes.add(Capsule3d::new(0.5, 1.))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: SLATE_400.into(),
+            ..default()
+        })),
+        rider_body(&tuning, attract_start(&noise)),
+        CollisionLayers::new(
+            GameLayer::Player,
+            GameLayer::Default,
+        ),
+    ));
+}
+
+/// Nobody's watching a rider that's come to a
+/// stop or slipped under the terrain, so those
+/// runs start over from the top.
+fn restart_stalled_attract_run(
+    mut rider: Single<
+        (
+            &mut Position,
+            &mut LinearVelocity,
+            &mut GroundState,
+        ),
+        With<AttractRider>,
+    >,
+    noise: Res<LandChunkNoise>,
+    tuning: Res<GameTuning>,
+) {
+    let (position, linvel, state) = &mut *rider;
+    let stalled = linvel.length() < 5.;
+    let fell_through = position.y
+        < terrain_height(&noise, position.x, position.z)
+            - 5.;
+    if !stalled && !fell_through {
+        return;
+    }
+    position.0 = attract_start(&noise);
+    linvel.0 = Vec3::NEG_Z * tuning.start_speed;
+    **state = GroundState::default();
+}
+
+fn orbit_attract_camera(
+    mut cameras: Query<
+        &mut Transform,
+        (
+            With<PlayerFollowCamera>,
+            Without<AttractRider>,
+        ),
+    >,
+    rider: Single<&Transform, With<AttractRider>>,
+    time: Res<Time>,
+) {
+    let angle = time.elapsed_secs() / ORBIT_PERIOD * TAU;
+    let offset = Quat::from_rotation_y(angle)
+        * Vec3::new(0., 8., 20.);
+    for mut transform in &mut cameras {
+        transform.translation = rider.translation + offset;
+        transform.look_at(rider.translation, Vec3::Y);
+    }
+}
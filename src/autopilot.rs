@@ -0,0 +1,94 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppState, Riding,
+    crash::Crashed,
+    movement::{
+        ControlInput, ControlInputSet, GroundState,
+    },
+    playing::Player,
+    rivals::{dodge, obstacle_ahead, steer_towards},
+    terrain_chunking::Obstacle,
+};
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            drive_autopilot.in_set(ControlInputSet).run_if(
+                in_state(Riding).and(
+                    resource_exists::<Autopilot>
+                        .or(in_state(AppState::MainMenu)),
+                ),
+            ),
+        );
+    }
+}
+
+/// Takes the player's controls while it exists,
+/// and always rides the attract mode behind the
+/// main menu.
+///
+/// bevy_enhanced_input can't mock actions, so
+/// the bot fills in the same [`ControlInput`] the
+/// `Move` and `FastFall` actions would, the way
+/// replays do.
+#[derive(Resource, Debug)]
+pub struct Autopilot {
+    /// meters ahead to watch for obstacles
+    pub look_ahead: f32,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Self { look_ahead: 50. }
+    }
+}
+
+/// Steers around obstacles, otherwise straight
+/// down the hill, and fast falls back to the
+/// ground after every jump.
+fn drive_autopilot(
+    autopilot: Option<Res<Autopilot>>,
+    spatial_query: SpatialQuery,
+    mut players: Query<
+        (
+            &mut ControlInput,
+            &Position,
+            &LinearVelocity,
+            &GroundState,
+        ),
+        (With<Player>, Without<Crashed>),
+    >,
+    obstacles: Query<(), With<Obstacle>>,
+) {
+    let look_ahead = autopilot.map_or(
+        Autopilot::default().look_ahead,
+        |autopilot| autopilot.look_ahead,
+    );
+    for (mut input, position, linvel, state) in &mut players
+    {
+        *input = ControlInput::default();
+        let Ok(direction) = Dir3::new(linvel.0) else {
+            continue;
+        };
+
+        input.steer.x = match obstacle_ahead(
+            &spatial_query,
+            &obstacles,
+            position.0,
+            direction,
+            look_ahead,
+        ) {
+            Some(hit) => {
+                dodge(linvel.0, position.0, hit.point1)
+            }
+            None => steer_towards(linvel.0, Vec3::NEG_Z),
+        };
+        input.fast_fall =
+            !state.is_grounded() && linvel.y < 0.;
+    }
+}
@@ -0,0 +1,360 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_enhanced_input::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    chase_camera::ChaseCamera,
+    playing::{Player, PlayerFollowCamera},
+    settings::Settings,
+    split_screen::{PlayerDevice, PlayerSlot},
+    terrain_chunking::{LandChunkNoise, terrain_height},
+    tuning::GameTuning,
+};
+
+/// Meters a trackside camera stands above the
+/// snow.
+const TRACKSIDE_HEIGHT: f32 = 3.;
+
+pub struct CameraModesPlugin;
+
+impl Plugin for CameraModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_input_context::<CameraControls>()
+            .add_observer(bind_camera_controls)
+            .add_observer(cycle_camera_mode)
+            .add_systems(
+                OnExit(AppState::Playing),
+                restore_perspective,
+            )
+            .add_systems(
+                Update,
+                (
+                    match_projection,
+                    ride_along,
+                    follow_from_the_side,
+                    film_from_trackside,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// How a player's camera follows them, switched
+/// with the `CycleCamera` action.
+#[derive(
+    Component,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+)]
+pub enum CameraMode {
+    /// Behind the player, see [`ChaseCamera`]
+    #[default]
+    Chase,
+    /// From the player's head
+    FirstPerson,
+    /// Side on and orthographic, level with the
+    /// player, to check jumps and landings
+    /// without perspective skewing the angles
+    Side,
+    /// Fixed cameras beside the slope that zoom
+    /// in as the player goes past
+    Trackside,
+}
+
+impl CameraMode {
+    fn next(self) -> Self {
+        match self {
+            CameraMode::Chase => CameraMode::FirstPerson,
+            CameraMode::FirstPerson => CameraMode::Side,
+            CameraMode::Side => CameraMode::Trackside,
+            CameraMode::Trackside => CameraMode::Chase,
+        }
+    }
+}
+
+/// Where a camera in [`CameraMode::Trackside`] is
+/// currently standing.
+#[derive(Component, Default, Debug)]
+pub struct TracksideSpot {
+    spot: Option<Vec3>,
+    /// which side of the line the next spot goes
+    left: bool,
+}
+
+/// Camera input, available whether the player is
+/// on the ground or in the air.
+#[derive(InputContext)]
+pub struct CameraControls;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+struct CycleCamera;
+
+fn bind_camera_controls(
+    trigger: Trigger<Binding<CameraControls>>,
+    mut actions: Query<&mut Actions<CameraControls>>,
+    devices: Query<&PlayerDevice>,
+) {
+    let mut actions =
+        actions.get_mut(trigger.target()).unwrap();
+    let device = devices
+        .get(trigger.target())
+        .copied()
+        .unwrap_or(PlayerDevice::Any);
+    device.apply_gamepad(&mut actions);
+
+    device.bind_button::<_, CycleCamera>(
+        &mut actions,
+        [KeyCode::KeyC, KeyCode::ControlRight],
+        GamepadButton::Select,
+    );
+}
+
+/// Switches the camera of whoever pressed it, and
+/// remembers the mode for the next run.
+fn cycle_camera_mode(
+    trigger: Trigger<Started<CycleCamera>>,
+    players: Query<&PlayerSlot, With<Player>>,
+    mut cameras: Query<
+        (
+            &PlayerSlot,
+            &mut CameraMode,
+            &mut ChaseCamera,
+            &Transform,
+        ),
+        With<PlayerFollowCamera>,
+    >,
+    mut settings: ResMut<Settings>,
+) {
+    let Ok(player_slot) = players.get(trigger.target())
+    else {
+        return;
+    };
+    for (slot, mut mode, mut chase, transform) in
+        &mut cameras
+    {
+        if slot != player_slot {
+            continue;
+        }
+        *mode = mode.next();
+        // swing back in from wherever the last
+        // mode left the camera
+        if *mode == CameraMode::Chase {
+            chase.pick_up(transform);
+        }
+        settings.camera_mode = *mode;
+    }
+}
+
+fn side_projection() -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scale: 0.01,
+        ..OrthographicProjection::default_3d()
+    })
+}
+
+fn perspective(tuning: &GameTuning) -> Projection {
+    Projection::Perspective(PerspectiveProjection {
+        fov: tuning.camera_fov,
+        ..default()
+    })
+}
+
+/// Switches cameras to an orthographic projection
+/// for [`CameraMode::Side`] and back to
+/// perspective for everything else.
+fn match_projection(
+    mut cameras: Query<(&mut Projection, &CameraMode)>,
+    tuning: Res<GameTuning>,
+) {
+    for (mut projection, mode) in &mut cameras {
+        let orthographic = matches!(
+            *projection,
+            Projection::Orthographic(_)
+        );
+        match (*mode == CameraMode::Side, orthographic) {
+            (true, false) => {
+                *projection = side_projection()
+            }
+            (false, true) => {
+                *projection = perspective(&tuning)
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The menus always look through a perspective
+/// camera, whatever mode the run ended in.
+fn restore_perspective(
+    mut cameras: Query<&mut Projection, With<CameraMode>>,
+    tuning: Res<GameTuning>,
+) {
+    for mut projection in &mut cameras {
+        if matches!(
+            *projection,
+            Projection::Orthographic(_)
+        ) {
+            *projection = perspective(&tuning);
+        }
+    }
+}
+
+fn ride_along(
+    mut cameras: Query<
+        (&mut Transform, &CameraMode, &PlayerSlot),
+        Without<Player>,
+    >,
+    players: Query<
+        (&Transform, &LinearVelocity, &PlayerSlot),
+        With<Player>,
+    >,
+    time: Res<Time>,
+) {
+    for (mut transform, mode, slot) in &mut cameras {
+        if *mode != CameraMode::FirstPerson {
+            continue;
+        }
+        let Some((player, linvel, _)) = players
+            .iter()
+            .find(|(.., player_slot)| *player_slot == slot)
+        else {
+            continue;
+        };
+        // just under the top of the capsule, whose
+        // inside isn't drawn
+        transform.translation =
+            player.translation + Vec3::Y * 0.6;
+        let Ok(heading) = Dir3::new(linvel.0) else {
+            continue;
+        };
+        let rotation = Transform::IDENTITY
+            .looking_to(heading, Vec3::Y);
+        transform.rotation.smooth_nudge(
+            &rotation.rotation,
+            10.,
+            time.delta_secs(),
+        );
+    }
+}
+
+/// Side on from the left, for checking jump arcs
+/// and landing angles. The projection is
+/// orthographic, so only the direction matters,
+/// not the distance.
+fn follow_from_the_side(
+    mut cameras: Query<
+        (&mut Transform, &CameraMode, &PlayerSlot),
+        Without<Player>,
+    >,
+    players: Query<(&Transform, &PlayerSlot), With<Player>>,
+    time: Res<Time>,
+) {
+    for (mut transform, mode, slot) in &mut cameras {
+        if *mode != CameraMode::Side {
+            continue;
+        }
+        let Some((player, _)) = players
+            .iter()
+            .find(|(_, player_slot)| *player_slot == slot)
+        else {
+            continue;
+        };
+        transform.translation.smooth_nudge(
+            &(player.translation + Vec3::new(-10., 0., 0.)),
+            10.,
+            time.delta_secs(),
+        );
+        transform.look_at(player.translation, Vec3::Y);
+    }
+}
+
+/// Puts a camera up beside the slope ahead of the
+/// player and zooms in on them as they go past,
+/// then cuts to the next one.
+fn film_from_trackside(
+    mut cameras: Query<
+        (
+            &mut Transform,
+            &mut Projection,
+            &mut TracksideSpot,
+            &CameraMode,
+            &PlayerSlot,
+        ),
+        Without<Player>,
+    >,
+    players: Query<
+        (&Transform, &LinearVelocity, &PlayerSlot),
+        With<Player>,
+    >,
+    noise: Res<LandChunkNoise>,
+    tuning: Res<GameTuning>,
+) {
+    for (
+        mut transform,
+        mut projection,
+        mut trackside,
+        mode,
+        slot,
+    ) in &mut cameras
+    {
+        if *mode != CameraMode::Trackside {
+            trackside.spot = None;
+            continue;
+        }
+        let Some((player, linvel, _)) = players
+            .iter()
+            .find(|(.., player_slot)| *player_slot == slot)
+        else {
+            continue;
+        };
+        let heading = Dir3::new(linvel.with_y(0.))
+            .unwrap_or(Dir3::NEG_Z);
+
+        // cut once the player is well past the
+        // camera, or it was left behind by a restart
+        let passed = trackside.spot.is_none_or(|spot| {
+            (spot - player.translation).dot(*heading)
+                < -tuning.trackside_offset
+                || spot.distance(player.translation)
+                    > tuning.trackside_lead * 2.
+        });
+        if passed {
+            let side =
+                if trackside.left { -1. } else { 1. };
+            trackside.left = !trackside.left;
+            let mut spot = player.translation
+                + heading * tuning.trackside_lead
+                + heading.cross(Vec3::Y)
+                    * side
+                    * tuning.trackside_offset;
+            spot.y = terrain_height(&noise, spot.x, spot.z)
+                + TRACKSIDE_HEIGHT;
+            trackside.spot = Some(spot);
+        }
+        let Some(spot) = trackside.spot else {
+            continue;
+        };
+
+        *transform = Transform::from_translation(spot)
+            .looking_at(player.translation, Vec3::Y);
+        // keep the player about the same size on
+        // screen however far away they are
+        if let Projection::Perspective(perspective) =
+            &mut *projection
+        {
+            let distance =
+                spot.distance(player.translation);
+            perspective.fov = (2. * (4. / distance).atan())
+                .clamp(0.1, tuning.camera_max_fov);
+        }
+    }
+}
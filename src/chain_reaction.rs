@@ -0,0 +1,228 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppState, scoring::RunStats,
+    terrain_chunking::Obstacle, tuning::GameTuning,
+};
+
+pub struct ChainReactionPlugin;
+
+impl Plugin for ChainReactionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(detonate).add_systems(
+            FixedUpdate,
+            burn_fuses.run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// Destroys the target [`Obstacle`] and knocks
+/// over every obstacle around it.
+#[derive(Event)]
+pub struct Detonate {
+    /// velocity of whatever set it off
+    pub velocity: Vec3,
+}
+
+/// A knocked over obstacle that will detonate
+/// when this finishes.
+#[derive(Component)]
+pub struct Fuse(Timer);
+
+/// The velocity each of `candidates` is kicked
+/// with when an obstacle at `origin` goes off.
+///
+/// Only candidates within `radius` are kicked.
+/// They're pushed away from `origin` and a little
+/// upwards, harder the closer they are.
+pub fn chain_kicks(
+    origin: Vec3,
+    radius: f32,
+    strength: f32,
+    candidates: impl IntoIterator<Item = (Entity, Vec3)>,
+) -> Vec<(Entity, Vec3)> {
+    candidates
+        .into_iter()
+        .filter_map(|(entity, position)| {
+            let offset = position - origin;
+            let distance = offset.length();
+            if distance > radius {
+                return None;
+            }
+            let falloff = 1. - distance / radius;
+            let direction = (offset.normalize_or(Vec3::Y)
+                + Vec3::Y * 0.5)
+                .normalize();
+            Some((entity, direction * strength * falloff))
+        })
+        .collect()
+}
+
+fn detonate(
+    trigger: Trigger<Detonate>,
+    mut commands: Commands,
+    obstacles: Query<
+        (Entity, &GlobalTransform, Has<Fuse>),
+        With<Obstacle>,
+    >,
+    mut stats: ResMut<RunStats>,
+    tuning: Res<GameTuning>,
+) {
+    let Ok((_, transform, chained)) =
+        obstacles.get(trigger.target())
+    else {
+        return;
+    };
+    // no longer an obstacle, so anything else
+    // setting it off before it's gone does nothing
+    commands.entity(trigger.target()).remove::<Obstacle>();
+    if chained {
+        stats.chain_destructions += 1;
+    }
+
+    let candidates = obstacles
+        .iter()
+        .filter(|(entity, _, fused)| {
+            !fused && *entity != trigger.target()
+        })
+        .map(|(entity, transform, _)| {
+            (entity, transform.translation())
+        });
+    for (entity, kick) in chain_kicks(
+        transform.translation(),
+        tuning.chain_radius,
+        tuning.chain_kick,
+        candidates,
+    ) {
+        commands.entity(entity).insert((
+            RigidBody::Dynamic,
+            LinearVelocity(kick),
+            // topple over away from the blast
+            AngularVelocity(Vec3::Y.cross(kick) * 0.1),
+            Fuse(Timer::from_seconds(
+                tuning.chain_fuse_duration,
+                TimerMode::Once,
+            )),
+        ));
+    }
+    commands.entity(trigger.target()).despawn();
+}
+
+fn burn_fuses(
+    mut commands: Commands,
+    mut fuses: Query<(Entity, &mut Fuse, &LinearVelocity)>,
+    time: Res<Time>,
+) {
+    for (entity, mut fuse, linvel) in &mut fuses {
+        if fuse.0.tick(time.delta()).just_finished() {
+            commands.trigger_targets(
+                Detonate { velocity: linvel.0 },
+                entity,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kicks(candidates: &[Vec3]) -> Vec<(Entity, Vec3)> {
+        chain_kicks(
+            Vec3::ZERO,
+            10.,
+            20.,
+            candidates.iter().enumerate().map(
+                |(index, position)| {
+                    (
+                        Entity::from_raw(index as u32),
+                        *position,
+                    )
+                },
+            ),
+        )
+    }
+
+    #[test]
+    fn only_obstacles_in_range_are_kicked() {
+        let kicked = kicks(&[
+            Vec3::X * 5.,
+            Vec3::X * 10.,
+            Vec3::X * 10.5,
+            Vec3::new(20., 0., 20.),
+        ]);
+        let entities: Vec<_> = kicked
+            .iter()
+            .map(|(entity, _)| *entity)
+            .collect();
+        assert_eq!(
+            entities,
+            vec![Entity::from_raw(0), Entity::from_raw(1)]
+        );
+        // right on the edge of the blast barely moves
+        assert!(kicked[1].1.length() < 1e-4);
+    }
+
+    #[test]
+    fn kicks_push_away_and_up_harder_when_closer() {
+        let kicked =
+            kicks(&[Vec3::NEG_Z * 2., Vec3::X * 8.]);
+        let (_, near) = kicked[0];
+        let (_, far) = kicked[1];
+        assert!(near.z < 0. && near.y > 0.);
+        assert!(far.x > 0. && far.y > 0.);
+        assert!(near.length() > far.length());
+        assert!((near.length() - 20. * 0.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn obstacles_at_the_origin_go_straight_up() {
+        let kicked = kicks(&[Vec3::ZERO]);
+        let (_, kick) = kicked[0];
+        assert!(
+            kick.normalize().abs_diff_eq(Vec3::Y, 1e-6)
+        );
+        assert!((kick.length() - 20.).abs() < 1e-4);
+    }
+
+    fn detonating_world() -> World {
+        let mut world = World::new();
+        world.init_resource::<RunStats>();
+        world.insert_resource(GameTuning::default());
+        world.add_observer(detonate);
+        world
+    }
+
+    #[test]
+    fn obstacles_only_detonate_once() {
+        let mut world = detonating_world();
+        let fused = world
+            .spawn((
+                Obstacle,
+                Fuse(Timer::from_seconds(
+                    1.,
+                    TimerMode::Once,
+                )),
+                GlobalTransform::default(),
+            ))
+            .id();
+        // a fuse running out and the player
+        // plowing through on the same tick
+        let mut commands = world.commands();
+        for _ in 0..2 {
+            commands.trigger_targets(
+                Detonate {
+                    velocity: Vec3::ZERO,
+                },
+                fused,
+            );
+        }
+        world.flush();
+        assert_eq!(
+            world.resource::<RunStats>().chain_destructions,
+            1
+        );
+        assert!(world.get_entity(fused).is_err());
+    }
+}
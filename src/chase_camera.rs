@@ -0,0 +1,261 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    camera_modes::CameraMode,
+    movement::Landed,
+    playing::Player,
+    split_screen::PlayerSlot,
+    terrain_chunking::{LandChunkNoise, terrain_height},
+    tuning::GameTuning,
+};
+
+pub struct ChaseCameraPlugin;
+
+impl Plugin for ChaseCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Playing),
+            pick_up_from_current_view,
+        )
+        .add_systems(
+            Update,
+            (
+                add_landing_trauma,
+                chase_players,
+                widen_with_speed,
+                decay_trauma,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// How shaken up a player is, from 0 to 1. Their
+/// camera shakes with the square of it, so small
+/// knocks barely register.
+#[derive(Component, Default, Debug)]
+pub struct Trauma(f32);
+
+impl Trauma {
+    pub fn add(&mut self, amount: f32) {
+        self.0 = (self.0 + amount.max(0.)).min(1.);
+    }
+}
+
+/// Where a follow camera would be without any
+/// shake, eased towards a spot behind the player
+/// along their heading.
+#[derive(Component, Default, Debug)]
+pub struct ChaseCamera {
+    position: Vec3,
+    look_target: Vec3,
+    /// cameras that haven't been placed yet snap
+    /// straight into position
+    placed: bool,
+}
+
+impl ChaseCamera {
+    /// Eases in from wherever `transform` has the
+    /// camera now.
+    pub fn pick_up(&mut self, transform: &Transform) {
+        self.position = transform.translation;
+        self.look_target = transform.translation
+            + transform.forward() * 10.;
+        self.placed = true;
+    }
+}
+
+/// Eases into the run from wherever the camera
+/// was looking on the main menu.
+fn pick_up_from_current_view(
+    mut cameras: Query<(&Transform, &mut ChaseCamera)>,
+) {
+    for (transform, mut chase) in &mut cameras {
+        chase.pick_up(transform);
+    }
+}
+
+/// Landings that hit the slope hard shake the
+/// camera.
+fn add_landing_trauma(
+    mut landed: EventReader<Landed>,
+    mut players: Query<&mut Trauma>,
+    tuning: Res<GameTuning>,
+) {
+    for landing in landed.read() {
+        let Ok(mut trauma) =
+            players.get_mut(landing.entity)
+        else {
+            continue;
+        };
+        let impact = -landing.velocity.dot(landing.normal);
+        trauma.add(
+            (impact / tuning.camera_hard_landing_speed)
+                .powi(2),
+        );
+    }
+}
+
+fn chase_players(
+    mut cameras: Query<
+        (
+            &mut Transform,
+            &mut ChaseCamera,
+            &CameraMode,
+            &PlayerSlot,
+        ),
+        Without<Player>,
+    >,
+    players: Query<
+        (
+            &Transform,
+            &LinearVelocity,
+            &Trauma,
+            &PlayerSlot,
+        ),
+        With<Player>,
+    >,
+    noise: Res<LandChunkNoise>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+    real_time: Res<Time<Real>>,
+) {
+    let dt = time.delta_secs();
+    for (mut transform, mut chase, mode, slot) in
+        &mut cameras
+    {
+        if *mode != CameraMode::Chase {
+            continue;
+        }
+        // cameras of knocked out players stay put
+        let Some((player, linvel, trauma, _)) = players
+            .iter()
+            .find(|(.., player_slot)| *player_slot == slot)
+        else {
+            continue;
+        };
+
+        let speed = linvel.length();
+        let heading = Dir3::new(linvel.with_y(0.))
+            .unwrap_or(Dir3::NEG_Z);
+        let right = heading.cross(Vec3::Y);
+        let offset = tuning.camera_offset;
+        let position = player.translation
+            + right * offset.x
+            + Vec3::Y * offset.y
+            - heading
+                * (offset.z
+                    + speed * tuning.camera_pullback);
+        let look_target = player.translation
+            + linvel.0 * tuning.camera_look_ahead;
+
+        if chase.placed {
+            chase.position.smooth_nudge(
+                &position,
+                tuning.camera_stiffness,
+                dt,
+            );
+            // the look target leads, so it can't lag
+            // as far behind
+            chase.look_target.smooth_nudge(
+                &look_target,
+                tuning.camera_stiffness * 2.,
+                dt,
+            );
+        } else {
+            chase.position = position;
+            chase.look_target = look_target;
+            chase.placed = true;
+        }
+        let ground = terrain_height(
+            &noise,
+            chase.position.x,
+            chase.position.z,
+        ) + tuning.camera_min_clearance;
+        chase.position.y = chase.position.y.max(ground);
+
+        // shake in real time, so it keeps going
+        // through hitstop
+        let shake = trauma.0 * trauma.0;
+        let t = real_time.elapsed_secs();
+        let jitter = Vec3::new(
+            (t * 37.).sin(),
+            (t * 43. + 1.).sin(),
+            (t * 29. + 2.).sin(),
+        );
+        transform.translation = chase.position
+            + jitter * shake * tuning.camera_shake_offset;
+        transform.look_at(chase.look_target, Vec3::Y);
+        transform.rotate_local_z(
+            (t * 31. + 3.).sin()
+                * shake
+                * tuning.camera_shake_roll,
+        );
+    }
+}
+
+/// Widens the field of view with the player's
+/// speed, in every mode that doesn't zoom itself.
+fn widen_with_speed(
+    mut cameras: Query<
+        (
+            &mut Projection,
+            &CameraMode,
+            &PlayerSlot,
+        ),
+        Without<Player>,
+    >,
+    players: Query<
+        (&LinearVelocity, &PlayerSlot),
+        With<Player>,
+    >,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (mut projection, mode, slot) in &mut cameras {
+        // trackside zooms on its own, side is
+        // orthographic
+        if matches!(
+            mode,
+            CameraMode::Trackside | CameraMode::Side
+        ) {
+            continue;
+        }
+        let Some((linvel, _)) = players
+            .iter()
+            .find(|(_, player_slot)| *player_slot == slot)
+        else {
+            continue;
+        };
+        if let Projection::Perspective(perspective) =
+            &mut *projection
+        {
+            let t = (linvel.length()
+                / tuning.camera_fov_speed)
+                .clamp(0., 1.);
+            perspective.fov.smooth_nudge(
+                &tuning
+                    .camera_fov
+                    .lerp(tuning.camera_max_fov, t),
+                2.,
+                time.delta_secs(),
+            );
+        }
+    }
+}
+
+fn decay_trauma(
+    mut traumas: Query<&mut Trauma>,
+    real_time: Res<Time<Real>>,
+    tuning: Res<GameTuning>,
+) {
+    for mut trauma in &mut traumas {
+        trauma.0 = (trauma.0
+            - tuning.camera_trauma_decay
+                * real_time.delta_secs())
+        .max(0.);
+    }
+}
@@ -0,0 +1,108 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppState,
+    movement::Rider,
+    playing::Invulnerable,
+    terrain_chunking::{LandChunkNoise, terrain_height},
+    tuning::GameTuning,
+};
+
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(start_tumbling).add_systems(
+            FixedUpdate,
+            recover_from_crash
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// A rider has been knocked over and tumbles
+/// as a dynamic body until this finishes.
+#[derive(Component)]
+pub struct Crashed(Timer);
+
+impl Crashed {
+    pub fn new(tuning: &GameTuning) -> Self {
+        Self(Timer::from_seconds(
+            tuning.crash_duration,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// Hands the rider over to the physics solver,
+/// sending them head over heels in the direction
+/// they were going.
+fn start_tumbling(
+    trigger: Trigger<OnAdd, Crashed>,
+    mut players: Query<
+        (&LinearVelocity, &mut AngularVelocity),
+        With<Rider>,
+    >,
+    mut commands: Commands,
+) {
+    let Ok((linvel, mut angvel)) =
+        players.get_mut(trigger.target())
+    else {
+        return;
+    };
+    info!("crashed");
+    // nose down around the rider's right axis
+    angvel.0 = Vec3::Y.cross(linvel.0) * 0.2;
+    commands
+        .entity(trigger.target())
+        .insert((RigidBody::Dynamic, LockedAxes::new()));
+}
+
+/// Puts the rider back on their feet on top of
+/// the terrain once the tumble is over.
+fn recover_from_crash(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &mut Crashed,
+            &mut Position,
+            &mut Rotation,
+            &mut LinearVelocity,
+            &mut AngularVelocity,
+        ),
+        With<Rider>,
+    >,
+    noise: Res<LandChunkNoise>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (
+        entity,
+        mut crashed,
+        mut position,
+        mut rotation,
+        mut linvel,
+        mut angvel,
+    ) in &mut players
+    {
+        if !crashed.0.tick(time.delta()).finished() {
+            continue;
+        }
+        position.y =
+            terrain_height(&noise, position.x, position.z)
+                + 1.;
+        rotation.0 = Quat::IDENTITY;
+        linvel.0 =
+            Vec3::NEG_Z * tuning.crash_recovery_speed;
+        angvel.0 = Vec3::ZERO;
+        commands.entity(entity).remove::<Crashed>().insert(
+            (
+                RigidBody::Kinematic,
+                LockedAxes::ROTATION_LOCKED,
+                Invulnerable::new(&tuning),
+            ),
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    AppState, GameLayer,
+    chain_reaction::Detonate,
+    playing::Player,
+    terrain_chunking::{Obstacle, ObstacleKind},
+    tuning::GameTuning,
+};
+
+pub struct DebrisPlugin;
+
+impl Plugin for DebrisPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LiveDebris>()
+            .add_observer(shatter)
+            .add_systems(
+                OnExit(AppState::Playing),
+                forget_live_debris,
+            )
+            .add_systems(
+                FixedUpdate,
+                expire_debris
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// A piece of a destroyed [`Obstacle`].
+#[derive(Component)]
+pub struct Debris(Timer);
+
+/// Live debris, oldest first, so the oldest
+/// pieces can make room once
+/// [`GameTuning::max_debris`] is reached.
+#[derive(Resource, Default)]
+struct LiveDebris(VecDeque<Entity>);
+
+/// Replaces a detonated obstacle with a cube of
+/// smaller cubes that fly off with whatever set
+/// it off.
+fn shatter(
+    trigger: Trigger<Detonate>,
+    mut commands: Commands,
+    obstacles: Query<
+        (
+            &GlobalTransform,
+            &ObstacleKind,
+            &MeshMaterial3d<StandardMaterial>,
+        ),
+        With<Obstacle>,
+    >,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut live_debris: ResMut<LiveDebris>,
+    tuning: Res<GameTuning>,
+) {
+    let Ok((transform, kind, material)) =
+        obstacles.get(trigger.target())
+    else {
+        return;
+    };
+
+    let piece_size = kind.size() / 2.;
+    let mesh = meshes.add(Cuboid::from_length(piece_size));
+    let center = transform.translation();
+    for x in [-1., 1.] {
+        for y in [-1., 1.] {
+            for z in [-1., 1.] {
+                let offset =
+                    Vec3::new(x, y, z) * piece_size / 2.;
+                let id = commands
+                    .spawn((
+                        Name::new("Debris"),
+                        Debris(Timer::from_seconds(
+                            tuning.debris_lifetime,
+                            TimerMode::Once,
+                        )),
+                        StateScoped(AppState::Playing),
+                        Mesh3d(mesh.clone()),
+                        material.clone(),
+                        Transform::from_translation(
+                            center + offset,
+                        ),
+                        RigidBody::Dynamic,
+                        Collider::cuboid(
+                            piece_size, piece_size,
+                            piece_size,
+                        ),
+                        CollisionLayers::new(
+                            GameLayer::Debris,
+                            [
+                                GameLayer::Default,
+                                GameLayer::Debris,
+                            ],
+                        ),
+                        LinearVelocity(
+                            trigger.velocity
+                                + offset.normalize()
+                                    * tuning.debris_spread,
+                        ),
+                    ))
+                    .id();
+                live_debris.0.push_back(id);
+            }
+        }
+    }
+
+    while live_debris.0.len() > tuning.max_debris {
+        if let Some(oldest) = live_debris.0.pop_front() {
+            commands.entity(oldest).try_despawn();
+        }
+    }
+}
+
+/// Despawns debris that's been around too long or
+/// has been left far behind.
+fn expire_debris(
+    mut commands: Commands,
+    mut debris: Query<(
+        Entity,
+        &mut Debris,
+        &GlobalTransform,
+    )>,
+    players: Query<&GlobalTransform, With<Player>>,
+    mut live_debris: ResMut<LiveDebris>,
+    tuning: Res<GameTuning>,
+    time: Res<Time>,
+) {
+    for (entity, mut piece, transform) in &mut debris {
+        let expired = piece.0.tick(time.delta()).finished();
+        // from the closest player
+        let distance = players
+            .iter()
+            .map(|player| {
+                transform
+                    .translation()
+                    .distance(player.translation())
+            })
+            .reduce(f32::min)
+            .unwrap_or(0.);
+        if expired || distance > tuning.debris_max_distance
+        {
+            commands.entity(entity).despawn();
+            live_debris.0.retain(|live| *live != entity);
+        }
+    }
+}
+
+/// Debris is `StateScoped`, so it's already gone.
+fn forget_live_debris(mut live_debris: ResMut<LiveDebris>) {
+    live_debris.0.clear();
+}
@@ -9,6 +9,8 @@ use bevy_inspector_egui::{
     bevy_egui::EguiPlugin, quick::WorldInspectorPlugin,
 };
 
+use crate::autopilot::Autopilot;
+
 pub struct DevToolsPlugin;
 
 impl Plugin for DevToolsPlugin {
@@ -23,9 +25,14 @@ impl Plugin for DevToolsPlugin {
         )
         .add_systems(
             Update,
-            toggle_debug_ui.run_if(input_just_pressed(
-                KeyCode::Backquote,
-            )),
+            (
+                toggle_debug_ui.run_if(input_just_pressed(
+                    KeyCode::Backquote,
+                )),
+                toggle_autopilot.run_if(
+                    input_just_pressed(KeyCode::F1),
+                ),
+            ),
         );
     }
 }
@@ -33,3 +40,14 @@ impl Plugin for DevToolsPlugin {
 fn toggle_debug_ui(mut options: ResMut<UiDebugOptions>) {
     options.toggle();
 }
+
+fn toggle_autopilot(
+    mut commands: Commands,
+    autopilot: Option<Res<Autopilot>>,
+) {
+    if autopilot.is_some() {
+        commands.remove_resource::<Autopilot>();
+    } else {
+        commands.insert_resource(Autopilot::default());
+    }
+}
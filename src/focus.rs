@@ -0,0 +1,164 @@
+use bevy::{color::palettes::tailwind::*, prelude::*};
+
+use crate::{
+    AppState, PauseState,
+    movement::ControlInput,
+    playing::Player,
+    scoring::{LandingQuality, LandingRated},
+    split_screen::PlayerSlot,
+    time_dilation::{DilationSource, TimeDilation},
+    tuning::GameTuning,
+};
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                // `ControlInput` goes stale while
+                // paused
+                use_focus
+                    .run_if(in_state(PauseState::Running)),
+                refill_focus_on_landing,
+                update_focus_meter,
+            )
+                .chain()
+                .run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+/// How much focus a player has left, from 0 to 1.
+/// Holding `Focus` slows time down for everyone
+/// until it runs out.
+#[derive(Component, Debug)]
+pub struct FocusMeter(pub f32);
+
+impl Default for FocusMeter {
+    fn default() -> Self {
+        Self(1.)
+    }
+}
+
+impl FocusMeter {
+    pub fn refill(&mut self, amount: f32) {
+        self.0 = (self.0 + amount).min(1.);
+    }
+}
+
+#[derive(Component)]
+struct FocusMeterFill;
+
+/// The focus meter of the player in `slot`, shown
+/// in their `camera`'s viewport under their
+/// lives.
+pub fn focus_meter_hud(
+    slot: usize,
+    camera: Entity,
+) -> impl Bundle {
+    (
+        StateScoped(AppState::Playing),
+        Name::new(format!(
+            "Player {} Focus Meter",
+            slot + 1
+        )),
+        UiTargetCamera(camera),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(30.),
+            top: Val::Px(75.),
+            width: Val::Px(200.),
+            height: Val::Px(12.),
+            ..default()
+        },
+        BackgroundColor(SLATE_800.into()),
+        children![(
+            FocusMeterFill,
+            PlayerSlot(slot),
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                ..default()
+            },
+            BackgroundColor(VIOLET_400.into()),
+        )],
+    )
+}
+
+/// Drains each focusing player's meter in real
+/// time while `Focus` is held, so slowing time
+/// down doesn't make it last longer. Time slows
+/// down while anyone is focusing.
+fn use_focus(
+    mut players: Query<
+        (&ControlInput, &mut FocusMeter),
+        With<Player>,
+    >,
+    mut time_dilation: ResMut<TimeDilation>,
+    real_time: Res<Time<Real>>,
+    tuning: Res<GameTuning>,
+) {
+    let mut focusing = false;
+    for (input, mut meter) in &mut players {
+        if input.focus && meter.0 > 0. {
+            meter.0 = (meter.0
+                - tuning.focus_drain_rate
+                    * real_time.delta_secs())
+            .max(0.);
+            focusing = true;
+        }
+    }
+    let slowed =
+        time_dilation.is_active(DilationSource::SlowMotion);
+    if focusing && !slowed {
+        // hitstop outranks this, so hits still
+        // freeze the frame while focusing
+        time_dilation.request(
+            DilationSource::SlowMotion,
+            tuning.focus_time_scale,
+            None,
+        );
+    } else if !focusing && slowed {
+        time_dilation.clear(DilationSource::SlowMotion);
+    }
+}
+
+/// Better landings give back more focus.
+fn refill_focus_on_landing(
+    mut landings: EventReader<LandingRated>,
+    mut meters: Query<&mut FocusMeter>,
+    tuning: Res<GameTuning>,
+) {
+    for LandingRated { entity, quality } in landings.read()
+    {
+        let Ok(mut meter) = meters.get_mut(*entity) else {
+            continue;
+        };
+        meter.refill(
+            tuning.focus_landing_refill
+                * quality.points() as f32
+                / LandingQuality::Perfect.points() as f32,
+        );
+    }
+}
+
+fn update_focus_meter(
+    players: Query<
+        (&FocusMeter, &PlayerSlot),
+        Changed<FocusMeter>,
+    >,
+    mut fills: Query<
+        (&mut Node, &PlayerSlot),
+        With<FocusMeterFill>,
+    >,
+) {
+    for (meter, player_slot) in &players {
+        for (mut node, slot) in &mut fills {
+            if slot == player_slot {
+                node.width = Val::Percent(meter.0 * 100.);
+            }
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use bevy::{
+    color::palettes::tailwind::*, ecs::spawn::SpawnWith,
+    prelude::*,
+};
+
+use crate::{
+    AppState,
+    leaderboard::{
+        GameMode, Leaderboard, LeaderboardEntry,
+        LeaderboardKey, PlayerName, SubmitLeaderboardEntry,
+    },
+    scoring::{LandingQuality, RunStats},
+    terrain_chunking::TerrainSeed,
+    ui::{FONT_PATH, main_menu_text_button},
+};
+
+pub struct GameOverPlugin;
+
+impl Plugin for GameOverPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::GameOver),
+            spawn_results_screen,
+        );
+    }
+}
+
+/// Records the finished run on the leaderboard
+/// and shows how it went.
+fn spawn_results_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    stats: Res<RunStats>,
+    leaderboard: Res<Leaderboard>,
+    player_name: Res<PlayerName>,
+    seed: Res<TerrainSeed>,
+    mode: Res<GameMode>,
+    mut submissions: EventWriter<SubmitLeaderboardEntry>,
+) {
+    let key = LeaderboardKey {
+        seed: seed.0,
+        mode: *mode,
+    };
+    let score = stats.score();
+    // see `RunStats`
+    let team = *mode == GameMode::SplitScreen;
+    let new_record = leaderboard
+        .entries(&key)
+        .first()
+        .is_none_or(|best| score > best.score);
+    submissions.write(SubmitLeaderboardEntry {
+        key,
+        entry: LeaderboardEntry::new(
+            player_name.0.clone(),
+            score,
+            stats.distance,
+            stats.top_speed,
+        ),
+    });
+
+    let font = asset_server.load(FONT_PATH);
+    let font_retry = font.clone();
+    let font_menu = font.clone();
+
+    let line = |text: String, font_size: f32| {
+        (
+            Text::new(text),
+            TextFont {
+                font: font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(SLATE_50.into()),
+        )
+    };
+
+    let landings: Vec<_> = LandingQuality::ALL
+        .iter()
+        .map(|quality| {
+            line(
+                format!(
+                    "{}: {}",
+                    quality.label(),
+                    stats.landings.count(*quality)
+                ),
+                20.,
+            )
+        })
+        .collect();
+
+    commands.spawn((
+        StateScoped(AppState::GameOver),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        children![(
+            Node {
+                width: Val::Px(350.),
+                height: Val::Auto,
+                margin: UiRect::all(Val::Px(125.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::End,
+                row_gap: Val::Px(5.),
+                ..default()
+            },
+            Children::spawn((
+                Spawn((
+                    Text::new(if new_record {
+                        "New Record!"
+                    } else {
+                        ""
+                    }),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 28.,
+                        ..default()
+                    },
+                    TextColor(YELLOW_400.into()),
+                )),
+                Spawn(line("Game Over".to_string(), 42.)),
+                Spawn(line(
+                    if team {
+                        format!("Team Score: {score}")
+                    } else {
+                        format!("Score: {score}")
+                    },
+                    28.,
+                )),
+                Spawn(line(
+                    if team {
+                        "Everyone's points count together, \
+                         distances are the furthest rider's"
+                            .to_string()
+                    } else {
+                        String::new()
+                    },
+                    16.,
+                )),
+                Spawn(line(
+                    format!(
+                        "Distance: {} m",
+                        stats.distance as u32
+                    ),
+                    20.,
+                )),
+                Spawn(line(
+                    format!(
+                        "Top Speed: {} m/s",
+                        stats.top_speed as u32
+                    ),
+                    20.,
+                )),
+                Spawn(line(
+                    format!(
+                        "Chain Reactions: {}",
+                        stats.chain_destructions
+                    ),
+                    20.,
+                )),
+                Spawn(line(
+                    format!(
+                        "Near Misses: {}",
+                        stats.near_misses
+                    ),
+                    20.,
+                )),
+                SpawnWith(
+                    move |parent: &mut ChildSpawner| {
+                        for landing in landings {
+                            parent.spawn(landing);
+                        }
+                    }
+                ),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Retry", font_retry,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<AppState>,
+                            >| {
+                                next_state
+                                    .set(AppState::Playing);
+                            },
+                        );
+                }),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Main Menu",
+                            font_menu,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<AppState>,
+                            >| {
+                                next_state.set(
+                                    AppState::MainMenu,
+                                );
+                            },
+                        );
+                }),
+            ))
+        )],
+    ));
+}
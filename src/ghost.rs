@@ -0,0 +1,563 @@
+use std::collections::BTreeMap;
+
+use avian3d::prelude::*;
+use bevy::{color::palettes::tailwind::*, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    leaderboard::{
+        GameMode, LeaderboardEntrySubmitted, LeaderboardKey,
+    },
+    playing::Player,
+    split_screen::single_player,
+    terrain_chunking::TerrainSeed,
+    ui::FONT_PATH,
+};
+
+/// Fixed ticks between two samples of a track.
+const SAMPLE_TICKS: usize = 4;
+
+#[cfg(not(target_arch = "wasm32"))]
+const GHOSTS_PATH: &str = "ghosts.ron";
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BestGhosts::load())
+            .init_resource::<GhostRecorder>()
+            .init_resource::<PendingGhost>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                (start_recording, spawn_ghost)
+                    .run_if(single_player),
+            )
+            .add_systems(
+                OnExit(AppState::Playing),
+                finish_recording.run_if(single_player),
+            )
+            .add_systems(
+                FixedUpdate,
+                (record_ghost, advance_ghost).run_if(
+                    in_state(AppState::Playing)
+                        .and(single_player),
+                ),
+            )
+            .add_systems(
+                Update,
+                (move_ghost, update_ghost_delta_text)
+                    .run_if(
+                        in_state(AppState::Playing)
+                            .and(single_player),
+                    ),
+            )
+            .add_systems(Update, store_best_ghost);
+    }
+}
+
+/// A recorded run: the player's position and
+/// velocity every few fixed ticks, in whole
+/// centimeters and centimeters per second.
+///
+/// The player's rotation is locked, so that's all
+/// that's needed to play it back. The velocity
+/// lets playback follow the curve between
+/// samples rather than cutting corners.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GhostTrack {
+    /// seconds between samples
+    pub interval: f32,
+    points: Vec<[i32; 3]>,
+    /// empty for tracks saved before velocities
+    /// were recorded
+    #[serde(default)]
+    velocities: Vec<[i32; 3]>,
+}
+
+impl GhostTrack {
+    fn push(&mut self, translation: Vec3, velocity: Vec3) {
+        self.points.push(
+            (translation * 100.).round().as_ivec3().into(),
+        );
+        self.velocities.push(
+            (velocity * 100.).round().as_ivec3().into(),
+        );
+    }
+
+    fn last(&self) -> Option<Vec3> {
+        self.point(self.points.len().checked_sub(1)?)
+    }
+
+    fn point(&self, index: usize) -> Option<Vec3> {
+        self.points.get(index).map(|point| {
+            IVec3::from(*point).as_vec3() / 100.
+        })
+    }
+
+    fn velocity(&self, index: usize) -> Option<Vec3> {
+        self.velocities.get(index).map(|velocity| {
+            IVec3::from(*velocity).as_vec3() / 100.
+        })
+    }
+
+    /// Where the run was `seconds` after it
+    /// started, or `None` once it's over.
+    pub fn position_at(
+        &self,
+        seconds: f32,
+    ) -> Option<Vec3> {
+        let at = seconds / self.interval;
+        let index = at as usize;
+        let t = at.fract();
+        let current = self.point(index)?;
+        let Some(next) = self.point(index + 1) else {
+            return Some(current);
+        };
+        let (Some(from), Some(to)) = (
+            self.velocity(index),
+            self.velocity(index + 1),
+        ) else {
+            return Some(current.lerp(next, t));
+        };
+        // cubic Hermite, with the velocities as the
+        // tangents
+        let (t2, t3) = (t * t, t * t * t);
+        Some(
+            current * (2. * t3 - 3. * t2 + 1.)
+                + from * self.interval * (t3 - 2. * t2 + t)
+                + next * (-2. * t3 + 3. * t2)
+                + to * self.interval * (t3 - t2),
+        )
+    }
+
+    /// How fast the run was going `seconds` after
+    /// it started, in m/s.
+    pub fn speed_at(&self, seconds: f32) -> Option<f32> {
+        let at = seconds / self.interval;
+        let index = at as usize;
+        let current = self.velocity(index)?;
+        let next =
+            self.velocity(index + 1).unwrap_or(current);
+        Some(current.lerp(next, at.fract()).length())
+    }
+
+    /// Meters travelled along the track by each
+    /// sample.
+    fn distances(&self) -> Vec<f32> {
+        let mut travelled = 0.;
+        (0..self.points.len())
+            .map(|index| {
+                if index > 0 {
+                    travelled += self
+                        .point(index)
+                        .unwrap()
+                        .distance(
+                            self.point(index - 1).unwrap(),
+                        );
+                }
+                travelled
+            })
+            .collect()
+    }
+
+    /// Seconds into the run at which it had
+    /// travelled `distance` meters, given its
+    /// [`distances`](Self::distances), or `None`
+    /// if it never got that far.
+    ///
+    /// Distance travelled only ever grows, unlike
+    /// any one coordinate, however the run weaved
+    /// or climbed.
+    fn seconds_reaching(
+        &self,
+        distances: &[f32],
+        distance: f32,
+    ) -> Option<f32> {
+        let index =
+            distances.partition_point(|d| *d < distance);
+        if index >= distances.len() {
+            return None;
+        }
+        if index == 0 {
+            return Some(0.);
+        }
+        let before = distances[index - 1];
+        let after = distances[index];
+        let fraction = (distance - before)
+            / (after - before).max(f32::EPSILON);
+        Some((index as f32 - 1. + fraction) * self.interval)
+    }
+}
+
+/// The best run's track for every seed and game
+/// mode, kept in `ghosts.ron` rather than the
+/// leaderboard to keep that small.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct BestGhosts(BTreeMap<LeaderboardKey, GhostTrack>);
+
+impl BestGhosts {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load() -> Self {
+        let Ok(contents) =
+            std::fs::read_to_string(GHOSTS_PATH)
+        else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(?error, "failed to parse ghosts");
+            Self::default()
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self) {
+        let result = ron::ser::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| {
+                std::fs::write(GHOSTS_PATH, contents)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(error) = result {
+            warn!(?error, "failed to save ghosts");
+        }
+    }
+
+    // TODO: localStorage
+    #[cfg(target_arch = "wasm32")]
+    fn save(&self) {}
+}
+
+/// The track of the run currently being played.
+#[derive(Resource, Default)]
+struct GhostRecorder {
+    track: GhostTrack,
+    ticks: usize,
+    /// meters travelled along the track so far
+    distance: f32,
+}
+
+/// The track of the last finished run, kept until
+/// the leaderboard decides if it's the new best.
+#[derive(Resource, Default)]
+struct PendingGhost(Option<(LeaderboardKey, GhostTrack)>);
+
+#[derive(Component)]
+pub struct Ghost {
+    track: GhostTrack,
+    distances: Vec<f32>,
+    /// fixed seconds since the run started
+    elapsed: f32,
+}
+
+#[derive(Component)]
+struct GhostDeltaText;
+
+fn start_recording(
+    mut recorder: ResMut<GhostRecorder>,
+    time: Res<Time<Fixed>>,
+) {
+    *recorder = GhostRecorder {
+        track: GhostTrack {
+            interval: time.timestep().as_secs_f32()
+                * SAMPLE_TICKS as f32,
+            ..default()
+        },
+        ..default()
+    };
+}
+
+fn record_ghost(
+    mut recorder: ResMut<GhostRecorder>,
+    player: Single<
+        (&Transform, &LinearVelocity),
+        With<Player>,
+    >,
+) {
+    let (transform, linvel) = *player;
+    if recorder.ticks.is_multiple_of(SAMPLE_TICKS) {
+        if let Some(last) = recorder.track.last() {
+            recorder.distance +=
+                last.distance(transform.translation);
+        }
+        recorder
+            .track
+            .push(transform.translation, linvel.0);
+    }
+    recorder.ticks += 1;
+}
+
+fn finish_recording(
+    mut recorder: ResMut<GhostRecorder>,
+    mut pending: ResMut<PendingGhost>,
+    seed: Res<TerrainSeed>,
+    mode: Res<GameMode>,
+) {
+    let key = LeaderboardKey {
+        seed: seed.0,
+        mode: *mode,
+    };
+    pending.0 =
+        Some((key, std::mem::take(&mut recorder.track)));
+}
+
+/// Keeps the pending track if the run it belongs
+/// to just took first place.
+fn store_best_ghost(
+    mut submitted: EventReader<LeaderboardEntrySubmitted>,
+    mut pending: ResMut<PendingGhost>,
+    mut ghosts: ResMut<BestGhosts>,
+) {
+    for event in submitted.read() {
+        if event.rank != Some(0) {
+            continue;
+        }
+        let Some((key, track)) =
+            pending.0.take_if(|(key, _)| *key == event.key)
+        else {
+            continue;
+        };
+        ghosts.0.insert(key, track);
+        ghosts.save();
+    }
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    ghosts: Res<BestGhosts>,
+    seed: Res<TerrainSeed>,
+    mode: Res<GameMode>,
+) {
+    let Some(track) = ghosts.0.get(&LeaderboardKey {
+        seed: seed.0,
+        mode: *mode,
+    }) else {
+        return;
+    };
+    let Some(first) = track.point(0) else {
+        return;
+    };
+
+    commands.spawn((
+        Name::new("Ghost"),
+        StateScoped(AppState::Playing),
+        Ghost {
+            track: track.clone(),
+            distances: track.distances(),
+            elapsed: 0.,
+        },
+        Mesh3d(meshes.add(Capsule3d::new(0.5, 1.))),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: SKY_400.with_alpha(0.3).into(),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        })),
+        Transform::from_translation(first),
+    ));
+
+    commands.spawn((
+        StateScoped(AppState::Playing),
+        Node {
+            width: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            padding: UiRect::top(Val::Px(30.)),
+            ..default()
+        },
+        children![(
+            Text::new(""),
+            TextFont {
+                font: asset_server.load(FONT_PATH),
+                font_size: 28.,
+                ..default()
+            },
+            TextColor(SLATE_50.into()),
+            GhostDeltaText,
+        )],
+    ));
+}
+
+fn advance_ghost(
+    mut ghosts: Query<&mut Ghost>,
+    time: Res<Time>,
+) {
+    for mut ghost in &mut ghosts {
+        ghost.elapsed += time.delta_secs();
+    }
+}
+
+/// Places the ghost between fixed ticks so it
+/// moves as smoothly as the interpolated player.
+fn move_ghost(
+    mut ghosts: Query<(
+        &Ghost,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+    time: Res<Time<Fixed>>,
+) {
+    for (ghost, mut transform, mut visibility) in
+        &mut ghosts
+    {
+        let seconds = ghost.elapsed
+            + time.overstep_fraction()
+                * time.timestep().as_secs_f32();
+        match ghost.track.position_at(seconds) {
+            Some(position) => {
+                transform.translation = position
+            }
+            // the ghost's run is over
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+/// Shows how many seconds the player is ahead (-)
+/// or behind (+) the ghost, comparing when each
+/// of them had travelled as far as the player has
+/// now, and how fast the ghost was going there.
+fn update_ghost_delta_text(
+    ghost: Single<&Ghost>,
+    player: Single<&Transform, With<Player>>,
+    recorder: Res<GhostRecorder>,
+    time: Res<Time<Fixed>>,
+    mut text: Single<
+        (&mut Text, &mut TextColor),
+        With<GhostDeltaText>,
+    >,
+) {
+    let distance = recorder.distance
+        + recorder.track.last().map_or(0., |last| {
+            last.distance(player.translation)
+        });
+    let Some(ghost_seconds) = ghost
+        .track
+        .seconds_reaching(&ghost.distances, distance)
+    else {
+        text.0.0 = "".to_string();
+        return;
+    };
+    let player_seconds = recorder.ticks as f32
+        * time.timestep().as_secs_f32();
+    let delta = player_seconds - ghost_seconds;
+
+    text.0.0 = match ghost.track.speed_at(ghost_seconds) {
+        Some(speed) => {
+            format!("{delta:+.2}s  {speed:.0} m/s")
+        }
+        None => format!("{delta:+.2}s"),
+    };
+    text.1.0 = if delta > 0. {
+        RED_400.into()
+    } else {
+        GREEN_400.into()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A track along `points`, going at a steady
+    /// speed from one to the next.
+    fn track(points: &[Vec3]) -> GhostTrack {
+        let mut track = GhostTrack {
+            interval: 1.,
+            ..default()
+        };
+        for (index, point) in points.iter().enumerate() {
+            let next =
+                points.get(index + 1).unwrap_or(point);
+            track.push(*point, *next - *point);
+        }
+        track
+    }
+
+    #[test]
+    fn positions_are_kept_to_the_centimeter() {
+        let track = track(&[Vec3::new(1.234, -5.678, 9.)]);
+        let position = track.position_at(0.).unwrap();
+        assert!(
+            position.distance(Vec3::new(1.23, -5.68, 9.))
+                < 1e-4
+        );
+        assert_eq!(track.position_at(1.), None);
+    }
+
+    #[test]
+    fn reaching_a_distance_survives_going_back_uphill() {
+        // down the hill, back up it, then down again
+        let track = track(&[
+            Vec3::new(0., 0., 0.),
+            Vec3::new(0., 0., -10.),
+            Vec3::new(0., 0., -5.),
+            Vec3::new(0., 0., -20.),
+        ]);
+        let distances = track.distances();
+        assert_eq!(distances, vec![0., 10., 15., 30.]);
+
+        let seconds = |distance| {
+            track.seconds_reaching(&distances, distance)
+        };
+        assert_eq!(seconds(0.), Some(0.));
+        assert_eq!(seconds(5.), Some(0.5));
+        assert_eq!(seconds(12.5), Some(1.5));
+        assert_eq!(seconds(22.5), Some(2.5));
+        assert_eq!(seconds(31.), None);
+    }
+
+    #[test]
+    fn playback_follows_the_recorded_velocity() {
+        // a jump: up and forwards, then down and
+        // forwards
+        let mut track = GhostTrack {
+            interval: 1.,
+            ..default()
+        };
+        track.push(Vec3::ZERO, Vec3::new(0., 2., -4.));
+        track.push(
+            Vec3::new(0., 0., -4.),
+            Vec3::new(0., -2., -4.),
+        );
+
+        // halfway along it's above the straight line
+        // between the samples
+        let middle = track.position_at(0.5).unwrap();
+        assert!((middle.y - 0.5).abs() < 1e-4);
+        assert!((middle.z + 2.).abs() < 1e-4);
+        // and still goes through them
+        assert_eq!(track.position_at(0.), Some(Vec3::ZERO));
+        assert!(
+            track
+                .position_at(0.999)
+                .unwrap()
+                .distance(Vec3::new(0., 0., -4.))
+                < 0.01
+        );
+        assert!(
+            (track.speed_at(0.5).unwrap() - 4.).abs()
+                < 1e-4
+        );
+    }
+
+    #[test]
+    fn tracks_without_velocities_still_play_back() {
+        let track: GhostTrack = ron::from_str(
+            "(interval: 1., points: [(0, 0, 0), (0, 0, -400)])",
+        )
+        .unwrap();
+        assert_eq!(
+            track.position_at(0.5),
+            Some(Vec3::new(0., 0., -2.))
+        );
+        assert_eq!(track.speed_at(0.5), None);
+    }
+}
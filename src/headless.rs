@@ -0,0 +1,192 @@
+//! Runs the game without a window or renderer,
+//! with the [`Autopilot`] riding, to check
+//! balance and catch regressions from tests and
+//! scripts. See `examples/playtest.rs`.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::{
+    asset::AssetPlugin, gizmos::GizmoPlugin,
+    input::InputPlugin, prelude::*,
+    render::render_resource::Shader, scene::ScenePlugin,
+    state::app::StatesPlugin, time::TimeUpdateStrategy,
+    window::WindowFocused,
+};
+use bevy_enhanced_input::EnhancedInputPlugin;
+
+use crate::{
+    AppState, Riding,
+    attract::AttractPlugin,
+    autopilot::{Autopilot, AutopilotPlugin},
+    camera_modes::CameraModesPlugin,
+    chain_reaction::ChainReactionPlugin,
+    chase_camera::ChaseCameraPlugin,
+    crash::CrashPlugin,
+    debris::DebrisPlugin,
+    focus::FocusPlugin,
+    movement::MovementPlugin,
+    near_miss::NearMissPlugin,
+    pause::PausePlugin,
+    playing::{Player, PlayerFollowCamera, PlayingPlugin},
+    rivals::{RivalDifficulty, RivalsPlugin},
+    scoring::{LandingCounts, RunStats, ScoringPlugin},
+    settings::Settings,
+    split_screen::{PlayerSlot, SplitScreenPlugin},
+    terrain_chunking::{
+        LandChunkNoise, LandChunkPlugin, TerrainSeed,
+        terrain_height,
+    },
+    time_dilation::TimeDilationPlugin,
+    tuning::GameTuning,
+};
+
+/// How one autopilot run on one seed went.
+#[derive(Clone, Debug)]
+pub struct PlaytestReport {
+    pub seed: u32,
+    /// seconds the run lasted, at most the
+    /// requested duration
+    pub seconds: f32,
+    /// meters travelled down the hill
+    pub distance: f32,
+    /// m/s
+    pub top_speed: f32,
+    /// obstacles the autopilot ran into
+    pub hits: u32,
+    pub landings: LandingCounts,
+    /// ran out of lives before the time was up
+    pub game_over: bool,
+    /// ended up under the terrain, which the
+    /// controller should never allow
+    pub fell_through: bool,
+}
+
+/// A windowless app that starts straight into a
+/// run on `seed`, with the autopilot riding and
+/// time advancing by exactly `timestep` every
+/// update, however long the update really takes.
+pub fn playtest_app(
+    seed: u32,
+    tuning: GameTuning,
+    timestep: Duration,
+) -> App {
+    let seed = TerrainSeed(seed);
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        StatesPlugin,
+        AssetPlugin::default(),
+        // avian's collider constructors look for
+        // scenes
+        ScenePlugin,
+        InputPlugin,
+    ))
+    // gizmos load their shaders even without a
+    // renderer
+    .init_asset::<Shader>()
+    .add_plugins((
+        GizmoPlugin,
+        PhysicsPlugins::default(),
+        EnhancedInputPlugin,
+    ))
+    .init_asset::<Mesh>()
+    .init_asset::<StandardMaterial>()
+    .init_asset::<Image>()
+    .init_asset::<Font>()
+    // usually sent by the window
+    .add_event::<WindowFocused>()
+    .add_plugins((
+        PlayingPlugin,
+        MovementPlugin,
+        LandChunkPlugin,
+        ScoringPlugin,
+        PausePlugin,
+        TimeDilationPlugin,
+        CrashPlugin,
+        ChainReactionPlugin,
+        DebrisPlugin,
+        FocusPlugin,
+        NearMissPlugin,
+        SplitScreenPlugin,
+        (
+            RivalsPlugin,
+            AutopilotPlugin,
+            AttractPlugin,
+            ChaseCameraPlugin,
+            CameraModesPlugin,
+        ),
+    ))
+    .insert_resource(tuning)
+    .insert_resource(seed)
+    .insert_resource(LandChunkNoise::new(seed))
+    .insert_resource(RivalDifficulty::Off)
+    .insert_resource(Autopilot::default())
+    // rather than the player's settings file
+    .init_resource::<Settings>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(
+        timestep,
+    ))
+    .insert_resource(Time::<Fixed>::from_duration(timestep))
+    .insert_state(AppState::Playing)
+    .add_computed_state::<Riding>()
+    // what the HUD gets attached to
+    .world_mut()
+    .spawn((PlayerFollowCamera, PlayerSlot(0)));
+    app
+}
+
+/// Rides `seed` for up to `seconds` of game
+/// time, as fast as the machine allows.
+pub fn playtest(
+    seed: u32,
+    seconds: f32,
+    tuning: GameTuning,
+    timestep: Duration,
+) -> PlaytestReport {
+    let mut app = playtest_app(seed, tuning, timestep);
+    app.finish();
+    app.cleanup();
+
+    let mut game_over = false;
+    while app
+        .world()
+        .resource::<Time<Real>>()
+        .elapsed_secs()
+        < seconds
+    {
+        app.update();
+        if *app.world().resource::<State<AppState>>().get()
+            != AppState::Playing
+        {
+            game_over = true;
+            break;
+        }
+    }
+
+    let seconds =
+        app.world().resource::<Time<Real>>().elapsed_secs();
+    let mut players = app
+        .world_mut()
+        .query_filtered::<&Position, With<Player>>();
+    let noise = app.world().resource::<LandChunkNoise>();
+    let fell_through =
+        players.iter(app.world()).any(|position| {
+            position.y
+                < terrain_height(
+                    noise, position.x, position.z,
+                ) - 5.
+        });
+    let stats = app.world().resource::<RunStats>().clone();
+    PlaytestReport {
+        seed,
+        seconds,
+        distance: stats.distance,
+        top_speed: stats.top_speed,
+        hits: stats.hits,
+        landings: stats.landings,
+        game_over,
+        fell_through,
+    }
+}
@@ -0,0 +1,547 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    color::palettes::tailwind::*, ecs::spawn::SpawnWith,
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    terrain_chunking::TerrainSeed,
+    ui::{FONT_PATH, main_menu_text_button},
+};
+
+/// How many runs are kept per seed and game mode.
+pub const LEADERBOARD_SIZE: usize = 10;
+
+#[cfg(not(target_arch = "wasm32"))]
+const LEADERBOARD_PATH: &str = "leaderboard.ron";
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::load())
+            .init_resource::<LeaderboardView>()
+            .init_resource::<PlayerName>()
+            .init_resource::<GameMode>()
+            .add_event::<SubmitLeaderboardEntry>()
+            .add_event::<LeaderboardEntrySubmitted>()
+            .add_systems(Update, submit_entries)
+            .add_systems(
+                OnEnter(AppState::Leaderboard),
+                spawn_leaderboard_screen,
+            )
+            .add_systems(
+                Update,
+                refresh_leaderboard_screen.run_if(
+                    in_state(AppState::Leaderboard).and(
+                        resource_changed::<LeaderboardView>,
+                    ),
+                ),
+            );
+    }
+}
+
+/// The rule set a run was played with. Runs are
+/// only ranked against runs of the same mode.
+#[derive(
+    Resource,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum GameMode {
+    #[default]
+    Endless,
+    /// Endless with two or more local players,
+    /// scored as a team, see
+    /// [`RunStats`](crate::scoring::RunStats)
+    SplitScreen,
+}
+
+impl GameMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GameMode::Endless => "Endless",
+            GameMode::SplitScreen => "Split Screen",
+        }
+    }
+}
+
+/// Identifies a single leaderboard table.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub struct LeaderboardKey {
+    pub seed: u32,
+    pub mode: GameMode,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub score: u32,
+    /// meters travelled down the hill
+    pub distance: f32,
+    /// m/s
+    pub top_speed: f32,
+    /// seconds since the unix epoch
+    pub date: u64,
+}
+
+impl LeaderboardEntry {
+    /// Builds an entry dated now.
+    pub fn new(
+        name: impl Into<String>,
+        score: u32,
+        distance: f32,
+        top_speed: f32,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            score,
+            distance,
+            top_speed,
+            date: web_time::SystemTime::now()
+                .duration_since(web_time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Top runs for every seed and game mode that has
+/// been played.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    tables: BTreeMap<LeaderboardKey, Vec<LeaderboardEntry>>,
+}
+
+impl Leaderboard {
+    /// Entries for `key`, best first.
+    pub fn entries(
+        &self,
+        key: &LeaderboardKey,
+    ) -> &[LeaderboardEntry] {
+        self.tables
+            .get(key)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn keys(
+        &self,
+    ) -> impl Iterator<Item = &LeaderboardKey> {
+        self.tables.keys()
+    }
+
+    /// Would a run with `score` make it onto the
+    /// table for `key`?
+    pub fn qualifies(
+        &self,
+        key: &LeaderboardKey,
+        score: u32,
+    ) -> bool {
+        let entries = self.entries(key);
+        entries.len() < LEADERBOARD_SIZE
+            || entries
+                .last()
+                .is_some_and(|e| score > e.score)
+    }
+
+    /// Inserts `entry` into the table for `key`,
+    /// returning its zero-based rank if it made
+    /// the cut. Ties keep the earlier run
+    /// ahead.
+    pub fn submit(
+        &mut self,
+        key: LeaderboardKey,
+        entry: LeaderboardEntry,
+    ) -> Option<usize> {
+        let entries = self.tables.entry(key).or_default();
+        let rank = entries
+            .iter()
+            .position(|e| entry.score > e.score)
+            .unwrap_or(entries.len());
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        entries.insert(rank, entry);
+        entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load() -> Self {
+        let Ok(contents) =
+            std::fs::read_to_string(LEADERBOARD_PATH)
+        else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(?error, "failed to parse leaderboard");
+            Self::default()
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn save(&self) {
+        let result = ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::write(LEADERBOARD_PATH, contents)
+                .map_err(|e| e.to_string())
+        });
+        if let Err(error) = result {
+            warn!(?error, "failed to save leaderboard");
+        }
+    }
+
+    // TODO: localStorage
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn save(&self) {}
+}
+
+/// The name new leaderboard entries are recorded
+/// under.
+#[derive(Resource)]
+pub struct PlayerName(pub String);
+
+impl Default for PlayerName {
+    fn default() -> Self {
+        Self("Player".to_string())
+    }
+}
+
+/// Send this when a run ends to record it.
+#[derive(Event, Clone, Debug)]
+pub struct SubmitLeaderboardEntry {
+    pub key: LeaderboardKey,
+    pub entry: LeaderboardEntry,
+}
+
+/// Written after an entry is processed. `rank` is
+/// `None` if the run didn't make the table.
+#[derive(Event, Clone, Debug)]
+pub struct LeaderboardEntrySubmitted {
+    pub key: LeaderboardKey,
+    pub rank: Option<usize>,
+}
+
+fn submit_entries(
+    mut submissions: EventReader<SubmitLeaderboardEntry>,
+    mut submitted: EventWriter<LeaderboardEntrySubmitted>,
+    mut leaderboard: ResMut<Leaderboard>,
+) {
+    let mut changed = false;
+    for SubmitLeaderboardEntry { key, entry } in
+        submissions.read()
+    {
+        let rank = leaderboard.submit(*key, entry.clone());
+        changed |= rank.is_some();
+        submitted.write(LeaderboardEntrySubmitted {
+            key: *key,
+            rank,
+        });
+    }
+    if changed {
+        leaderboard.save();
+    }
+}
+
+/// Which table the leaderboard screen is showing.
+#[derive(Resource, Default)]
+struct LeaderboardView(Option<LeaderboardKey>);
+
+impl LeaderboardView {
+    fn cycle(
+        &mut self,
+        leaderboard: &Leaderboard,
+        forward: bool,
+    ) {
+        let keys: Vec<_> =
+            leaderboard.keys().copied().collect();
+        if keys.is_empty() {
+            return;
+        }
+        let index = self.0.and_then(|key| {
+            keys.iter().position(|k| *k == key)
+        });
+        // a table that hasn't been played yet sits
+        // before the first one
+        let next = match (index, forward) {
+            (None, true) => 0,
+            (None, false) => keys.len() - 1,
+            (Some(index), true) => (index + 1) % keys.len(),
+            (Some(index), false) => {
+                (index + keys.len() - 1) % keys.len()
+            }
+        };
+        self.0 = Some(keys[next]);
+    }
+}
+
+#[derive(Component)]
+struct LeaderboardTitle;
+
+#[derive(Component)]
+struct LeaderboardRows;
+
+fn spawn_leaderboard_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut view: ResMut<LeaderboardView>,
+    seed: Res<TerrainSeed>,
+    mode: Res<GameMode>,
+) {
+    // start on the table for the seed that will be
+    // played next
+    view.0 = Some(LeaderboardKey {
+        seed: seed.0,
+        mode: *mode,
+    });
+
+    let font = asset_server.load(FONT_PATH);
+    let font_prev = font.clone();
+    let font_next = font.clone();
+    let font_back = font.clone();
+    commands.spawn((
+        StateScoped(AppState::Leaderboard),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        children![(
+            Node {
+                width: Val::Auto,
+                height: Val::Auto,
+                margin: UiRect::all(Val::Px(125.)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            Children::spawn((
+                Spawn((
+                    Text::new(""),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 42.,
+                        ..default()
+                    },
+                    TextColor(SLATE_50.into()),
+                    LeaderboardTitle,
+                )),
+                Spawn((
+                    LeaderboardRows,
+                    Node {
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(5.),
+                        min_height: Val::Px(300.),
+                        ..default()
+                    },
+                )),
+                Spawn((
+                    Node {
+                        flex_direction: FlexDirection::Row,
+                        ..default()
+                    },
+                    Children::spawn((
+                        SpawnWith(
+                            |parent: &mut ChildSpawner| {
+                                parent
+                                    .spawn(main_menu_text_button(
+                                        "Prev", font_prev,
+                                    ))
+                                    .observe(cycle_view::<false>);
+                            },
+                        ),
+                        SpawnWith(
+                            |parent: &mut ChildSpawner| {
+                                parent
+                                    .spawn(main_menu_text_button(
+                                        "Next", font_next,
+                                    ))
+                                    .observe(cycle_view::<true>);
+                            },
+                        ),
+                        SpawnWith(
+                            |parent: &mut ChildSpawner| {
+                                parent
+                                    .spawn(main_menu_text_button(
+                                        "Back", font_back,
+                                    ))
+                                    .observe(
+                                        |_trigger: Trigger<
+                                            Pointer<Click>,
+                                        >,
+                                         mut next_state: ResMut<
+                                            NextState<AppState>,
+                                        >| {
+                                            next_state.set(
+                                                AppState::MainMenu,
+                                            );
+                                        },
+                                    );
+                            },
+                        ),
+                    )),
+                )),
+            ))
+        )],
+    ));
+}
+
+fn cycle_view<const FORWARD: bool>(
+    _trigger: Trigger<Pointer<Click>>,
+    leaderboard: Res<Leaderboard>,
+    mut view: ResMut<LeaderboardView>,
+) {
+    view.cycle(&leaderboard, FORWARD);
+}
+
+fn refresh_leaderboard_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    view: Res<LeaderboardView>,
+    leaderboard: Res<Leaderboard>,
+    mut title: Single<&mut Text, With<LeaderboardTitle>>,
+    rows: Single<Entity, With<LeaderboardRows>>,
+) {
+    let Some(key) = view.0 else {
+        return;
+    };
+    title.0 = format!(
+        "Seed {} - {}",
+        key.seed,
+        key.mode.label()
+    );
+
+    let font = asset_server.load(FONT_PATH);
+    let entries = leaderboard.entries(&key);
+
+    commands.entity(*rows).despawn_related::<Children>();
+    if entries.is_empty() {
+        commands.spawn((
+            ChildOf(*rows),
+            Text::new("No runs yet"),
+            TextFont {
+                font,
+                font_size: 20.,
+                ..default()
+            },
+            TextColor(SLATE_400.into()),
+        ));
+        return;
+    }
+    for (rank, entry) in entries.iter().enumerate() {
+        commands.spawn((
+            ChildOf(*rows),
+            Text::new(format!(
+                "{:>2}. {:<12} {:>7}  {:>6} m  {:>4} m/s  {}",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.distance as u32,
+                entry.top_speed as u32,
+                format_date(entry.date),
+            )),
+            TextFont {
+                font: font.clone(),
+                font_size: 20.,
+                ..default()
+            },
+            TextColor(SLATE_50.into()),
+        ));
+    }
+}
+
+/// `YYYY-MM-DD` for a unix timestamp, using
+/// Howard Hinnant's `civil_from_days`.
+fn format_date(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524
+        - doe / 146_096)
+        / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(seed: u32) -> LeaderboardKey {
+        LeaderboardKey {
+            seed,
+            mode: GameMode::Endless,
+        }
+    }
+
+    fn leaderboard(seeds: &[u32]) -> Leaderboard {
+        let mut leaderboard = Leaderboard::default();
+        for seed in seeds {
+            leaderboard.submit(
+                key(*seed),
+                LeaderboardEntry::new("Player", 1, 1., 1.),
+            );
+        }
+        leaderboard
+    }
+
+    #[test]
+    fn cycling_from_an_unplayed_table_starts_at_the_ends() {
+        let leaderboard = leaderboard(&[1, 2, 3]);
+
+        let mut view = LeaderboardView(Some(key(99)));
+        view.cycle(&leaderboard, true);
+        assert_eq!(view.0, Some(key(1)));
+
+        let mut view = LeaderboardView(Some(key(99)));
+        view.cycle(&leaderboard, false);
+        assert_eq!(view.0, Some(key(3)));
+    }
+
+    #[test]
+    fn cycling_wraps_around() {
+        let leaderboard = leaderboard(&[1, 2, 3]);
+        let mut view = LeaderboardView(Some(key(3)));
+        view.cycle(&leaderboard, true);
+        assert_eq!(view.0, Some(key(1)));
+        view.cycle(&leaderboard, false);
+        assert_eq!(view.0, Some(key(3)));
+    }
+}
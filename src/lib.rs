@@ -1,11 +1,34 @@
+use avian3d::prelude::*;
 use bevy::prelude::*;
 
 pub mod assets;
+pub mod attract;
+pub mod autopilot;
+pub mod camera_modes;
+pub mod chain_reaction;
+pub mod chase_camera;
+pub mod crash;
+pub mod debris;
 pub mod dev;
+pub mod focus;
+pub mod game_over;
+pub mod ghost;
+pub mod headless;
+pub mod leaderboard;
 pub mod movement;
+pub mod near_miss;
+pub mod pause;
 pub mod playing;
 pub mod postprocessing;
+pub mod replay;
+pub mod rivals;
+pub mod scoring;
+pub mod settings;
+pub mod split_screen;
 pub mod terrain_chunking;
+pub mod time_dilation;
+pub mod tuning;
+pub mod ui;
 
 #[derive(
     Clone, Eq, PartialEq, Debug, Hash, Default, States,
@@ -17,4 +40,55 @@ pub enum AppState {
     AssetLoading,
     Next,
     Playing,
+    Leaderboard,
+    GameOver,
+    /// Passes straight back to `Playing`, see
+    /// [`pause`].
+    Restarting,
+}
+
+#[derive(
+    Clone, Eq, PartialEq, Debug, Hash, Default, SubStates,
+)]
+#[source(AppState = AppState::Playing)]
+#[states(scoped_entities)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Active while riders are out on the slope:
+/// during runs and in the attract mode behind
+/// the main menu.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct Riding;
+
+impl ComputedStates for Riding {
+    type SourceStates = AppState;
+
+    fn compute(state: AppState) -> Option<Self> {
+        matches!(
+            state,
+            AppState::Playing | AppState::MainMenu
+        )
+        .then_some(Riding)
+    }
+}
+
+/// Collision layers. Everything that doesn't say
+/// otherwise is on [`GameLayer::Default`].
+#[derive(PhysicsLayer, Default)]
+pub enum GameLayer {
+    #[default]
+    Default,
+    Player,
+    /// Only collides with the world and other
+    /// debris, so the player rides straight
+    /// through it.
+    Debris,
+    /// Computer riders, which ride through
+    /// players and leave the obstacles
+    /// standing for them.
+    Rival,
 }
@@ -1,9 +1,6 @@
 use avian3d::prelude::*;
 use bevy::{
     color::palettes::tailwind::*,
-    core_pipeline::bloom::{
-        Bloom, BloomCompositeMode, BloomPrefilter,
-    },
     ecs::{
         error::{GLOBAL_ERROR_HANDLER, warn},
         spawn::SpawnWith,
@@ -13,7 +10,10 @@ use bevy::{
 use bevy_enhanced_input::EnhancedInputPlugin;
 use bevy_seedling::prelude::*;
 use bevy_skein::SkeinPlugin;
-use landing::*;
+use landing::{
+    ui::{MainMenuButton, main_menu_text_button},
+    *,
+};
 use vleue_kinetoscope::AnimatedImagePlugin;
 
 fn main() {
@@ -59,8 +59,31 @@ fn main() {
             playing::PlayingPlugin,
             movement::MovementPlugin,
             terrain_chunking::LandChunkPlugin,
+            leaderboard::LeaderboardPlugin,
+            ghost::GhostPlugin,
+            replay::ReplayPlugin,
+            scoring::ScoringPlugin,
+            game_over::GameOverPlugin,
+            pause::PausePlugin,
+            tuning::TuningPlugin,
+            time_dilation::TimeDilationPlugin,
+            (
+                crash::CrashPlugin,
+                chain_reaction::ChainReactionPlugin,
+                debris::DebrisPlugin,
+                focus::FocusPlugin,
+                near_miss::NearMissPlugin,
+                split_screen::SplitScreenPlugin,
+                rivals::RivalsPlugin,
+                autopilot::AutopilotPlugin,
+                attract::AttractPlugin,
+                chase_camera::ChaseCameraPlugin,
+                camera_modes::CameraModesPlugin,
+                settings::SettingsPlugin,
+            ),
         ))
         .init_state::<AppState>()
+        .add_computed_state::<Riding>()
         .add_systems(Startup, spawn_camera)
         .add_systems(OnEnter(AppState::Next), expect)
         .add_systems(
@@ -124,40 +147,20 @@ where
     }
 }
 
-fn spawn_camera(mut commands: Commands) {
+fn spawn_camera(
+    mut commands: Commands,
+    settings: Res<settings::Settings>,
+) {
+    // player one's camera sticks around for the
+    // menus, the others come and go with split
+    // screen runs
     commands.spawn((
-        playing::PlayerFollowCamera,
+        split_screen::player_camera(
+            0,
+            settings.camera_mode,
+        ),
         TransformInterpolation,
-        Camera3d::default(),
-        Camera {
-            hdr: true,
-            ..default()
-        },
-        Bloom {
-            intensity: 0.05,
-            low_frequency_boost: 0.7,
-            low_frequency_boost_curvature: 0.95,
-            high_pass_frequency: 1.0,
-            prefilter: BloomPrefilter {
-                threshold: 0.0,
-                threshold_softness: 0.0,
-            },
-            composite_mode:
-                BloomCompositeMode::EnergyConserving,
-            max_mip_dimension: 512,
-            scale: Vec2::ONE,
-        },
-        Transform::from_xyz(0., 3., 4.)
-            .looking_at(Vec3::ZERO, Vec3::Y),
-        DistanceFog {
-            // color: Color::srgb(0.25, 0.0, 0.25),
-            color: SLATE_950.into(),
-            falloff: FogFalloff::Linear {
-                start: 100.0,
-                end: 300.0,
-            },
-            ..default()
-        },
+        IsDefaultUiCamera,
         // Projection::Orthographic(OrthographicProjection
         // {     scaling_mode:
         //         bevy::render::camera::ScalingMode::AutoMin {
@@ -172,67 +175,13 @@ fn spawn_main_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     high_score: Res<playing::HighSpeed>,
-    mut time: ResMut<Time<Virtual>>,
+    player_count: Res<split_screen::PlayerCount>,
+    rival_difficulty: Res<rivals::RivalDifficulty>,
+    seed: Res<terrain_chunking::TerrainSeed>,
 ) {
-    time.set_relative_speed(1.);
     // commands.spawn(AnimatedImageController::play(
     //     asset_server.load("video/bevy.webp"),
     // ));
-    // commands.spawn((
-    //     playing::PlayerFollowCamera,
-    //     TransformInterpolation,
-    //     Camera3d::default(),
-    //     Camera {
-    //         hdr: true,
-    //         ..default()
-    //     },
-    //     Bloom {
-    //         intensity: 0.05,
-    //         low_frequency_boost: 0.7,
-    //         low_frequency_boost_curvature: 0.95,
-    //         high_pass_frequency: 1.0,
-    //         prefilter: BloomPrefilter {
-    //             threshold: 0.0,
-    //             threshold_softness: 0.0,
-    //         },
-    //         composite_mode:
-    //
-    // BloomCompositeMode::EnergyConserving,
-    //         max_mip_dimension: 512,
-    //         scale: Vec2::ONE,
-    //     },
-    //     Transform::from_xyz(0., 3., 4.)
-    //         .looking_at(Vec3::ZERO, Vec3::Y),
-    //     DistanceFog {
-    //         // color: Color::srgb(0.25, 0.0, 0.25),
-    //         color: SLATE_950.into(),
-    //         falloff: FogFalloff::Linear {
-    //             start: 100.0,
-    //             end: 300.0,
-    //         },
-    //         ..default()
-    //     },
-    //     // Projection::Orthographic(OrthographicProjection
-    //     // {     scaling_mode:
-    //     //
-    // bevy::render::camera::ScalingMode::AutoMin {
-    //     //             min_width: 1920.,
-    //     //             min_height: 1080.,
-    //     //         },
-    //     //
-    // ..OrthographicProjection::default_2d()
-    //     // }),
-    // ));
-    // commands.spawn((
-    //     playing::PlayerFollowCameraDebug,
-    //     Camera3d::default(),
-    //     Transform::from_xyz(-10., 0., 0.)
-    //         .looking_at(Vec3::ZERO, Vec3::Y),
-    //     Projection::Orthographic(OrthographicProjection {
-    //         scale: 0.01,
-    //         ..OrthographicProjection::default_3d()
-    //     }),
-    // ));
     let font = asset_server.load(
         "fonts/Alfa_Slab_One/AlfaSlabOne-Regular.ttf",
     );
@@ -240,6 +189,16 @@ fn spawn_main_menu(
     let font_two = font.clone();
     let font_three = font.clone();
     let font_four = font.clone();
+    let font_five = font.clone();
+    let font_six = font.clone();
+    let font_seven = font.clone();
+    let font_eight = font.clone();
+    let player_count_label =
+        split_screen::player_count_label(player_count.0);
+    let rival_difficulty_label =
+        rivals::rival_difficulty_label(*rival_difficulty);
+    let seed_label =
+        terrain_chunking::terrain_seed_label(*seed);
     commands.spawn((
         StateScoped(AppState::MainMenu),
         Node {
@@ -274,6 +233,36 @@ fn spawn_main_menu(
                             },
                         );
                 }),
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            &player_count_label,
+                            font_six,
+                        ))
+                        .observe(
+                            split_screen::cycle_player_count,
+                        );
+                }),
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            &rival_difficulty_label,
+                            font_seven,
+                        ))
+                        .observe(
+                            rivals::cycle_rival_difficulty,
+                        );
+                }),
+                SpawnWith(move |parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            &seed_label,
+                            font_eight,
+                        ))
+                        .observe(
+                            terrain_chunking::cycle_terrain_seed,
+                        );
+                }),
                 Spawn((
                     Name::new("High Speed Display".to_string()),
                     Button,
@@ -313,6 +302,24 @@ fn spawn_main_menu(
                         ]
                     )],
                 )),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Leaderboard", font_five,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<AppState>,
+                            >| {
+                                next_state.set(
+                                    AppState::Leaderboard,
+                                );
+                            },
+                        );
+                }),
                 SpawnWith(|parent: &mut ChildSpawner| {
                     parent.spawn(main_menu_text_button(
                         "Options", font_three,
@@ -341,30 +348,6 @@ fn spawn_main_menu(
     ));
 }
 
-#[derive(Component)]
-struct MainMenuButton;
-
-fn main_menu_text_button(
-    text: &str,
-    font: Handle<Font>,
-) -> impl Bundle {
-    (
-        Name::new(format!("{text} Button")),
-        Button,
-        MainMenuButton,
-        Node {
-            padding: UiRect::axes(
-                Val::Px(15.),
-                Val::Px(5.),
-            ),
-            ..default()
-        },
-        children![(
-            Text::new(text),
-            TextFont { font, ..default() }
-        )],
-    )
-}
 // fn startup(
 //     mut commands: Commands,
 //     asset_server: Res<AssetServer>,
@@ -1,80 +1,945 @@
-use std::{f32::consts::PI, ops::Neg};
+use std::ops::Neg;
 
-use avian3d::prelude::LinearVelocity;
-use bevy::prelude::*;
+use avian3d::prelude::*;
+use bevy::{color::palettes::tailwind::*, prelude::*};
 use bevy_enhanced_input::prelude::*;
 
-use crate::playing::Player;
+use crate::{
+    AppState, GameLayer, Riding,
+    autopilot::Autopilot,
+    crash::Crashed,
+    playing::{LastFrameVelocity, Player},
+    replay::ReplayPlayback,
+    scoring::LandingQuality,
+    split_screen::PlayerDevice,
+    terrain_chunking::Obstacle,
+    tuning::GameTuning,
+};
+
+/// Seconds a player has to be off the ground
+/// before their controls switch to [`Airborne`],
+/// so bumps and small lips don't flip them back
+/// and forth.
+const AIRBORNE_CONTEXT_DELAY: f32 = 0.2;
+
+/// Seconds a rider has to be in the air for
+/// touching down again to count as a [`Landed`].
+/// Shorter hops off bumps, and the tick before
+/// the first ground check after spawning, don't.
+const MIN_LANDING_AIRTIME: f32 = 0.25;
 
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
         app.add_input_context::<Grounded>()
-            .add_observer(bind_actions)
-            .add_observer(apply_movement);
+            .add_input_context::<Airborne>()
+            .add_observer(bind_grounded_actions)
+            .add_observer(bind_airborne_actions)
+            .add_event::<TookOff>()
+            .add_event::<Landed>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    update_ground_state
+                        .before(ControlInputSet),
+                    read_live_input
+                        .in_set(ControlInputSet)
+                        .run_if(
+                            in_state(AppState::Playing)
+                                .and(not(
+                                    resource_exists::<
+                                        ReplayPlayback,
+                                    >,
+                                ))
+                                .and(not(
+                                    resource_exists::<
+                                        Autopilot,
+                                    >,
+                                )),
+                        ),
+                    (apply_movement, jump, apply_pitch)
+                        .after(ControlInputSet),
+                )
+                    .run_if(in_state(Riding)),
+            )
+            .add_systems(
+                Update,
+                (
+                    switch_input_context,
+                    draw_landing_indicator,
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// Systems that write [`ControlInput`] for the
+/// current `FixedUpdate` tick. Anything reading
+/// [`ControlInput`] runs after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ControlInputSet;
+
+/// The inputs a rider acts on this fixed tick.
+///
+/// Gameplay reads this instead of the live
+/// [`Actions`] so that runs can be recorded and
+/// replayed tick for tick.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct ControlInput {
+    pub steer: Vec2,
+    pub fast_fall: bool,
+    /// held to charge a jump, which happens on
+    /// release
+    pub jump: bool,
+    /// nose up (+) or down (-) while airborne
+    pub pitch: f32,
+    /// held to slow down time, see
+    /// [`FocusMeter`](crate::focus::FocusMeter)
+    pub focus: bool,
+}
+
+/// Whether a rider is on the ground or in the
+/// air, updated from their `ShapeHits` at the
+/// start of every fixed tick. Times are
+/// `Time<Fixed>` elapsed seconds.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub enum GroundState {
+    Grounded {
+        since: f32,
+        normal: Vec3,
+        entity: Entity,
+    },
+    Airborne {
+        since: f32,
+    },
+}
+
+/// Riders start out on the snow. The normal and
+/// entity are filled in by the first ground
+/// check.
+impl Default for GroundState {
+    fn default() -> Self {
+        GroundState::Grounded {
+            since: 0.,
+            normal: Vec3::Y,
+            entity: Entity::PLACEHOLDER,
+        }
+    }
+}
+
+impl GroundState {
+    pub fn is_grounded(&self) -> bool {
+        matches!(self, GroundState::Grounded { .. })
+    }
+
+    /// The normal of the ground being ridden on.
+    pub fn normal(&self) -> Option<Vec3> {
+        match self {
+            GroundState::Grounded { normal, .. } => {
+                Some(*normal)
+            }
+            GroundState::Airborne { .. } => None,
+        }
     }
+
+    /// Seconds spent in the air so far, 0 while
+    /// grounded.
+    pub fn airtime(&self, now: f32) -> f32 {
+        match self {
+            GroundState::Grounded { .. } => 0.,
+            GroundState::Airborne { since } => now - since,
+        }
+    }
+}
+
+/// A rider left the ground.
+#[derive(Event, Debug)]
+pub struct TookOff {
+    pub entity: Entity,
+    pub velocity: Vec3,
 }
 
+/// A rider touched down after long enough in the
+/// air to count, not just a hop off a bump.
+#[derive(Event, Debug)]
+pub struct Landed {
+    pub entity: Entity,
+    pub normal: Vec3,
+    /// velocity coming into the landing
+    pub velocity: Vec3,
+    /// seconds spent in the air
+    pub airtime: f32,
+}
+
+/// Anything riding down the slope on its
+/// [`ControlInput`], players and rivals alike.
+#[derive(Component, Default, Debug)]
+pub struct Rider;
+
+/// Seconds the jump has been charging for.
+#[derive(Component, Default, Debug)]
+pub struct JumpCharge(pub f32);
+
+/// Radians the player is tilted nose up (+) or
+/// nose down (-) around their right axis.
+#[derive(Component, Default, Debug)]
+pub struct Pitch(pub f32);
+
+/// Active while the player is on the ground.
 #[derive(InputContext)]
 pub struct Grounded;
 
+/// Active while the player is in the air.
+#[derive(InputContext)]
+pub struct Airborne;
+
 #[derive(Debug, InputAction)]
 #[input_action(output = Vec2)]
 struct Move;
 
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+struct Jump;
+
+/// x steers, y pitches
+#[derive(Debug, InputAction)]
+#[input_action(output = Vec2)]
+struct AirControl;
+
+/// Available in every context.
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+struct Focus;
+
 // gravity += Jerk * time
 #[derive(Debug, InputAction)]
 #[input_action(output = bool)]
 pub struct FastFall;
 
-fn bind_actions(
+/// Jump and fast fall for the left and right
+/// halves of the keyboard.
+const JUMP_KEYS: [KeyCode; 2] =
+    [KeyCode::Space, KeyCode::Enter];
+const FOCUS_KEYS: [KeyCode; 2] =
+    [KeyCode::ShiftLeft, KeyCode::ShiftRight];
+
+fn bind_grounded_actions(
     trigger: Trigger<Binding<Grounded>>,
     // settings: Res<AppSettings>,
     mut actions: Query<&mut Actions<Grounded>>,
+    devices: Query<&PlayerDevice>,
 ) {
     let mut actions =
         actions.get_mut(trigger.target()).unwrap();
+    let device = devices
+        .get(trigger.target())
+        .copied()
+        .unwrap_or(PlayerDevice::Any);
+    device.apply_gamepad(&mut actions);
 
-    actions
-        .bind::<Move>()
-        .to((
-            Cardinal::wasd_keys(),
-            Axial::left_stick(),
-        ))
-        .with_modifiers((
-            DeadZone::default(),
-            SmoothNudge::default(),
-        ));
+    device.bind_stick::<_, Move>(&mut actions);
+    device.bind_button::<_, Jump>(
+        &mut actions,
+        JUMP_KEYS,
+        GamepadButton::South,
+    );
+    device.bind_button::<_, Focus>(
+        &mut actions,
+        FOCUS_KEYS,
+        GamepadButton::RightTrigger2,
+    );
+}
 
-    actions
-        .bind::<FastFall>()
-        .to((KeyCode::Space, GamepadButton::South));
+fn bind_airborne_actions(
+    trigger: Trigger<Binding<Airborne>>,
+    mut actions: Query<&mut Actions<Airborne>>,
+    devices: Query<&PlayerDevice>,
+) {
+    let mut actions =
+        actions.get_mut(trigger.target()).unwrap();
+    let device = devices
+        .get(trigger.target())
+        .copied()
+        .unwrap_or(PlayerDevice::Any);
+    device.apply_gamepad(&mut actions);
+
+    device.bind_stick::<_, AirControl>(&mut actions);
+    device.bind_button::<_, FastFall>(
+        &mut actions,
+        JUMP_KEYS,
+        GamepadButton::South,
+    );
+    device.bind_button::<_, Focus>(
+        &mut actions,
+        FOCUS_KEYS,
+        GamepadButton::RightTrigger2,
+    );
 }
 
-/// Apply movement when `Move` action considered
-/// fired.
+fn update_ground_state(
+    mut riders: Query<
+        (
+            Entity,
+            &ShapeHits,
+            &LastFrameVelocity,
+            &mut GroundState,
+        ),
+        Without<Crashed>,
+    >,
+    time: Res<Time>,
+    mut took_off: EventWriter<TookOff>,
+    mut landed: EventWriter<Landed>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, shape_hits, velocity, mut state) in
+        &mut riders
+    {
+        match (shape_hits.iter().next(), *state) {
+            (
+                Some(hit),
+                GroundState::Grounded { since, .. },
+            ) => {
+                *state = GroundState::Grounded {
+                    since,
+                    normal: hit.normal1,
+                    entity: hit.entity,
+                };
+            }
+            (Some(hit), GroundState::Airborne { .. }) => {
+                let airtime = state.airtime(now);
+                if airtime >= MIN_LANDING_AIRTIME {
+                    landed.write(Landed {
+                        entity,
+                        normal: hit.normal1,
+                        velocity: velocity.0,
+                        airtime,
+                    });
+                }
+                *state = GroundState::Grounded {
+                    since: now,
+                    normal: hit.normal1,
+                    entity: hit.entity,
+                };
+            }
+            (None, GroundState::Grounded { .. }) => {
+                took_off.write(TookOff {
+                    entity,
+                    velocity: velocity.0,
+                });
+                *state =
+                    GroundState::Airborne { since: now };
+            }
+            (None, GroundState::Airborne { .. }) => {}
+        }
+    }
+}
+
+/// Swaps the player's input context when they
+/// touch the ground, or once they've been in the
+/// air for [`AIRBORNE_CONTEXT_DELAY`].
+fn switch_input_context(
+    mut commands: Commands,
+    players: Query<
+        (
+            Entity,
+            &GroundState,
+            Has<Actions<Grounded>>,
+        ),
+        With<Player>,
+    >,
+    time: Res<Time<Fixed>>,
+) {
+    let now = time.elapsed_secs();
+    for (entity, state, has_grounded) in &players {
+        let grounded =
+            state.airtime(now) < AIRBORNE_CONTEXT_DELAY;
+        if grounded && !has_grounded {
+            commands
+                .entity(entity)
+                .remove::<Actions<Airborne>>()
+                .insert(Actions::<Grounded>::default());
+        } else if !grounded && has_grounded {
+            commands
+                .entity(entity)
+                .remove::<Actions<Grounded>>()
+                .insert(Actions::<Airborne>::default());
+        }
+    }
+}
+
+/// Samples whichever input context is active once
+/// per fixed tick.
+fn read_live_input(
+    mut players: Query<
+        (
+            Option<&Actions<Grounded>>,
+            Option<&Actions<Airborne>>,
+            &mut ControlInput,
+        ),
+        With<Player>,
+    >,
+) {
+    for (grounded, airborne, mut input) in &mut players {
+        *input = ControlInput::default();
+        if let Some(actions) = grounded {
+            input.steer = actions
+                .value::<Move>()
+                .map(|value| value.as_axis2d())
+                .unwrap_or_default();
+            input.jump = actions
+                .state::<Jump>()
+                .is_ok_and(|v| v == ActionState::Fired);
+            input.focus = actions
+                .state::<Focus>()
+                .is_ok_and(|v| v == ActionState::Fired);
+        }
+        if let Some(actions) = airborne {
+            let air_control = actions
+                .value::<AirControl>()
+                .map(|value| value.as_axis2d())
+                .unwrap_or_default();
+            input.steer = Vec2::new(air_control.x, 0.);
+            input.pitch = air_control.y;
+            input.fast_fall = actions
+                .state::<FastFall>()
+                .is_ok_and(|v| v == ActionState::Fired);
+            input.focus = actions
+                .state::<Focus>()
+                .is_ok_and(|v| v == ActionState::Fired);
+        }
+    }
+}
+
+/// Charges the jump while it's held on the ground
+/// and launches off the slope when it's released.
+fn jump(
+    mut players: Query<
+        (
+            &ControlInput,
+            &GroundState,
+            &mut JumpCharge,
+            &mut LinearVelocity,
+        ),
+        (With<Rider>, Without<Crashed>),
+    >,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    for (input, state, mut charge, mut linvel) in
+        &mut players
+    {
+        let ground = state.normal();
+        if input.jump && ground.is_some() {
+            charge.0 = (charge.0 + time.delta_secs())
+                .min(tuning.jump_max_charge);
+            continue;
+        }
+        if charge.0 > 0.
+            && let Some(ground) = ground
+        {
+            let t = charge.0 / tuning.jump_max_charge;
+            linvel.0 += ground
+                * tuning
+                    .jump_min_speed
+                    .lerp(tuning.jump_max_speed, t);
+        }
+        charge.0 = 0.;
+    }
+}
+
+/// Apply movement when there is steering input.
 fn apply_movement(
-    trigger: Trigger<Fired<Move>>,
-    mut players: Query<&mut LinearVelocity, With<Player>>,
+    mut players: Query<
+        (
+            &ControlInput,
+            &GroundState,
+            &mut LinearVelocity,
+        ),
+        (With<Rider>, Without<Crashed>),
+    >,
     time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    for (input, state, mut linvel) in &mut players {
+        if input.steer.x == 0. {
+            continue;
+        }
+        // turn around the slope we're riding on, or
+        // more gently around world up while airborne
+        let (normal, stick_x) = match state.normal() {
+            Some(normal) => (normal, input.steer.x),
+            None => (
+                Vec3::Y,
+                input.steer.x * tuning.air_steering,
+            ),
+        };
+        linvel.0 = steer(
+            linvel.0,
+            normal,
+            stick_x,
+            time.delta_secs(),
+            &tuning,
+        );
+    }
+}
+
+/// Tilts the player while airborne and bends
+/// their trajectory a little along with it, so
+/// they can line up with the slope they're about
+/// to land on.
+fn apply_pitch(
+    mut players: Query<
+        (
+            &ControlInput,
+            &GroundState,
+            &mut Pitch,
+            &mut LinearVelocity,
+            &mut Rotation,
+        ),
+        (With<Rider>, Without<Crashed>),
+    >,
+    time: Res<Time>,
+    tuning: Res<GameTuning>,
+) {
+    for (
+        input,
+        state,
+        mut pitch,
+        mut linvel,
+        mut rotation,
+    ) in &mut players
+    {
+        let Ok(right) = Dir3::new(linvel.0.cross(Vec3::Y))
+        else {
+            continue;
+        };
+        if state.is_grounded() {
+            // riders land upright
+            pitch.0 = 0.;
+            rotation.0 = Quat::IDENTITY;
+            continue;
+        }
+
+        let stick = input.pitch.clamp(-1., 1.);
+        let dt = time.delta_secs();
+        pitch.0 = (pitch.0
+            + stick * tuning.pitch_rate * dt)
+            .clamp(-tuning.max_pitch, tuning.max_pitch);
+        linvel.0 = Quat::from_axis_angle(
+            *right,
+            stick * tuning.pitch_velocity_bend * dt,
+        ) * linvel.0;
+        rotation.0 = Quat::from_axis_angle(*right, pitch.0);
+    }
+}
+
+/// Seconds between two points of the predicted
+/// jump arc.
+const ARC_STEP: f32 = 1. / 20.;
+/// How far ahead the jump arc is predicted.
+const ARC_SECONDS: f32 = 5.;
+
+/// Shows where the player will come down and how
+/// well they'd line up with the slope there,
+/// using the same rating as actual landings.
+fn draw_landing_indicator(
+    players: Query<
+        (
+            Entity,
+            &Position,
+            &LinearVelocity,
+            &GroundState,
+        ),
+        (With<Player>, Without<Crashed>),
+    >,
+    obstacles: Query<Entity, With<Obstacle>>,
+    spatial_query: SpatialQuery,
+    tuning: Res<GameTuning>,
+    mut gizmos: Gizmos,
 ) {
-    // let current_velocity =
-    // players.get_mut(trigger.target()).unwrap().0;
-
-    // info!(?current_velocity);
-
-    let angle = trigger.event().value.x.signum().neg() * PI
-        / 10.
-        * time.delta_secs();
-    let rotation_matrix = Mat3::from_cols(
-        Vec3::new(angle.cos(), 0., (-angle).sin()),
-        Vec3::new(0., 1., 0.),
-        Vec3::new(angle.sin(), 0., angle.cos()),
+    for (entity, position, linvel, state) in &players {
+        if state.is_grounded() {
+            continue;
+        }
+        let filter = SpatialQueryFilter::from_mask(
+            GameLayer::Default,
+        )
+        .with_excluded_entities(
+            obstacles.iter().chain([entity]),
+        );
+        draw_landing_for(
+            position.0,
+            linvel.0,
+            &filter,
+            &spatial_query,
+            &tuning,
+            &mut gizmos,
+        );
+    }
+}
+
+/// Follows the jump arc under `gravity` from
+/// `position`, shape casting the rider along
+/// each step, and rates the velocity the rider
+/// would have where it meets the ground.
+fn draw_landing_for(
+    position: Vec3,
+    velocity: Vec3,
+    filter: &SpatialQueryFilter,
+    spatial_query: &SpatialQuery,
+    tuning: &GameTuning,
+    gizmos: &mut Gizmos,
+) {
+    let rider = Collider::capsule(0.5, 1.);
+    let gravity = Vec3::NEG_Y * tuning.gravity;
+    let mut arc = vec![position];
+    let mut point = position;
+    let mut velocity = velocity;
+    let mut landing = None;
+    for _ in 0..(ARC_SECONDS / ARC_STEP) as usize {
+        let next_velocity = velocity + gravity * ARC_STEP;
+        let step =
+            (velocity + next_velocity) / 2. * ARC_STEP;
+        let Ok((direction, length)) =
+            Dir3::new_and_length(step)
+        else {
+            break;
+        };
+        if let Some(hit) = spatial_query.cast_shape(
+            &rider,
+            point,
+            Quat::IDENTITY,
+            direction,
+            &ShapeCastConfig::from_max_distance(length),
+            filter,
+        ) {
+            let fraction = hit.distance / length;
+            point += *direction * hit.distance;
+            arc.push(point);
+            landing = Some((
+                point,
+                hit.normal1,
+                velocity.lerp(next_velocity, fraction),
+            ));
+            break;
+        }
+        point += step;
+        velocity = next_velocity;
+        arc.push(point);
+    }
+    let Some((point, normal, velocity)) = landing else {
+        return;
+    };
+    let Ok(direction) = Dir3::new(velocity) else {
+        return;
+    };
+
+    let tangent = normal.cross(Vec3::X);
+    let quality = LandingQuality::from_alignment(
+        tangent.dot(*direction),
+    );
+    let color = match quality {
+        LandingQuality::Perfect => EMERALD_400,
+        LandingQuality::Good => LIME_400,
+        LandingQuality::Ok => AMBER_400,
+        LandingQuality::Meh => RED_400,
+    };
+
+    gizmos.linestrip(arc, color.with_alpha(0.3));
+    // the slope to match, where the bottom of the
+    // capsule touches it
+    let contact = point - Vec3::Y * 0.5 - normal * 0.5;
+    let lifted = contact + normal * 0.1;
+    gizmos.line(
+        lifted - tangent * 3.,
+        lifted + tangent * 3.,
+        color,
     );
+    // the direction the player would come in at
+    gizmos.arrow(lifted - *direction * 3., lifted, color);
+}
+
+/// Radians per second a rider going at `speed`
+/// turns at full steering.
+///
+/// Slow riders can't turn tighter than
+/// `min_turn_radius`, so the rate grows with
+/// speed at first. Fast riders are held back by
+/// how much sideways acceleration the edges
+/// grip, `carve_grip`, so past the crossover it
+/// falls off again.
+pub fn turn_rate(speed: f32, tuning: &GameTuning) -> f32 {
+    if speed <= 0. {
+        return 0.;
+    }
+    (speed / tuning.min_turn_radius)
+        .min(tuning.carve_grip / speed)
+}
+
+/// Turns `velocity` around `normal` for one step.
+///
+/// Positive `stick_x` turns right relative to the
+/// current heading, at a [`turn_rate`] scaled by
+/// how far the stick is pushed. Carving bleeds
+/// speed in proportion to how hard the stick is
+/// pushed.
+pub fn steer(
+    velocity: Vec3,
+    normal: Vec3,
+    stick_x: f32,
+    dt: f32,
+    tuning: &GameTuning,
+) -> Vec3 {
+    let Ok(axis) = Dir3::new(normal) else {
+        return velocity;
+    };
+    let stick_x = stick_x.clamp(-1., 1.);
+    let speed = velocity.length();
+
+    let angle =
+        stick_x.neg() * turn_rate(speed, tuning) * dt;
+    let turned =
+        Quat::from_axis_angle(*axis, angle) * velocity;
+
+    let speed_kept = (1.
+        - tuning.carve_speed_loss * stick_x.abs() * dt)
+        .max(0.);
+    turned * speed_kept
+}
+
+/// Advances a grounded rider's `velocity` by one
+/// step on a surface facing `normal`.
+///
+/// Gravity pulls along the slope, drag grows with
+/// the square of the speed and friction scales
+/// with how hard the rider is pressed into the
+/// surface. Drag and friction only ever slow the
+/// rider down, never reverse them, and the result
+/// never exceeds `terminal_velocity`.
+pub fn slide(
+    velocity: Vec3,
+    normal: Vec3,
+    dt: f32,
+    tuning: &GameTuning,
+) -> Vec3 {
+    let Ok(normal) = Dir3::new(normal) else {
+        return velocity;
+    };
+    let gravity = Vec3::NEG_Y * tuning.gravity;
+    let along_slope =
+        gravity - *normal * gravity.dot(*normal);
+    let velocity = velocity + along_slope * dt;
+
+    let speed = velocity.length();
+    let pressed_in = normal.y.max(0.) * tuning.gravity;
+    let slowdown = (tuning.drag * speed * speed
+        + tuning.friction * pressed_in)
+        * dt;
+    let speed = (speed - slowdown)
+        .clamp(0., tuning.terminal_velocity);
+    velocity.normalize_or_zero() * speed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 1. / 64.;
+
+    #[test]
+    fn turn_rate_rises_then_falls_off_with_speed() {
+        let tuning = GameTuning::default();
+        assert_eq!(turn_rate(0., &tuning), 0.);
+
+        let crossover = (tuning.carve_grip
+            * tuning.min_turn_radius)
+            .sqrt();
+        let peak = turn_rate(crossover, &tuning);
+        assert!(turn_rate(crossover / 2., &tuning) < peak);
+
+        // faster riders turn slower, all the way
+        // up through normal riding speeds
+        let mut last = peak;
+        for speed in [
+            crossover + 5.,
+            tuning.start_speed,
+            tuning.start_speed + 20.,
+            tuning.terminal_velocity,
+        ] {
+            let rate = turn_rate(speed, &tuning);
+            assert!(
+                rate < last,
+                "{speed} m/s turns at {rate}"
+            );
+            last = rate;
+        }
+    }
+
+    #[test]
+    fn positive_stick_turns_right_of_the_heading() {
+        let tuning = GameTuning::default();
+        // heading down the hill, -z, so right is +x
+        let turned = steer(
+            Vec3::NEG_Z * 50.,
+            Vec3::Y,
+            1.,
+            DT,
+            &tuning,
+        );
+        assert!(turned.x > 0.);
+        let turned = steer(
+            Vec3::NEG_Z * 50.,
+            Vec3::Y,
+            -1.,
+            DT,
+            &tuning,
+        );
+        assert!(turned.x < 0.);
+        // heading +x, right is +z
+        let turned =
+            steer(Vec3::X * 50., Vec3::Y, 1., DT, &tuning);
+        assert!(turned.z > 0.);
+    }
+
+    #[test]
+    fn turning_scales_with_stick_deflection() {
+        let tuning = GameTuning::default();
+        let velocity = Vec3::NEG_Z * 50.;
+        let angle = |stick_x| {
+            velocity.angle_between(steer(
+                velocity,
+                Vec3::Y,
+                stick_x,
+                DT,
+                &tuning,
+            ))
+        };
+        assert_eq!(angle(0.), 0.);
+        assert!((angle(0.5) * 2. - angle(1.)).abs() < 1e-4);
+        // pushing past full deflection doesn't help
+        assert_eq!(angle(2.), angle(1.));
+    }
+
+    #[test]
+    fn turning_stays_on_the_slope() {
+        let tuning = GameTuning::default();
+        let normal = Vec3::new(0., 1., 1.).normalize();
+        let velocity = normal.cross(Vec3::X) * 50.;
+        let turned =
+            steer(velocity, normal, 1., DT, &tuning);
+        assert!(turned.dot(normal).abs() < 1e-3);
+        assert!(turned.angle_between(velocity) > 0.);
+    }
+
+    #[test]
+    fn carving_bleeds_speed() {
+        let tuning = GameTuning::default();
+        let velocity = Vec3::NEG_Z * 50.;
+        let speed = |stick_x| {
+            steer(velocity, Vec3::Y, stick_x, DT, &tuning)
+                .length()
+        };
+        assert!((speed(0.) - 50.).abs() < 1e-4);
+        assert!(speed(0.5) < 50.);
+        assert!(speed(1.) < speed(0.5));
+    }
+
+    /// The normal of a slope going down towards
+    /// -z at `degrees`.
+    fn slope(degrees: f32) -> Vec3 {
+        let angle = degrees.to_radians();
+        Vec3::new(0., angle.cos(), -angle.sin())
+    }
+
+    fn slide_for(
+        seconds: f32,
+        velocity: Vec3,
+        normal: Vec3,
+        tuning: &GameTuning,
+    ) -> Vec3 {
+        (0..(seconds / DT) as usize).fold(
+            velocity,
+            |velocity, _| {
+                slide(velocity, normal, DT, tuning)
+            },
+        )
+    }
+
+    #[test]
+    fn steep_slopes_accelerate_faster_than_shallow_ones() {
+        let tuning = GameTuning::default();
+        let speed = |degrees| {
+            slide_for(
+                1.,
+                Vec3::ZERO,
+                slope(degrees),
+                &tuning,
+            )
+            .length()
+        };
+        assert!(speed(30.) > speed(15.));
+        assert!(speed(15.) > speed(5.));
+        // friction holds the rider on the gentlest
+        // slopes
+        assert_eq!(speed(1.), 0.);
+
+        let velocity =
+            slide_for(1., Vec3::ZERO, slope(30.), &tuning);
+        // down the slope, along its surface
+        assert!(velocity.z < 0. && velocity.y < 0.);
+        assert!(velocity.dot(slope(30.)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn drag_limits_speed_down_a_long_slope() {
+        let tuning = GameTuning::default();
+        let angle = 30_f32.to_radians();
+        // where drag and friction cancel out gravity
+        // along the slope
+        let terminal = ((tuning.gravity * angle.sin()
+            - tuning.friction
+                * tuning.gravity
+                * angle.cos())
+            / tuning.drag)
+            .sqrt();
+        assert!(terminal < tuning.terminal_velocity);
+
+        let from_rest =
+            slide_for(60., Vec3::ZERO, slope(30.), &tuning);
+        assert!(
+            (from_rest.length() - terminal).abs() < 0.5
+        );
+        // too fast slows down to the same speed
+        let overspeed = slope(30.).cross(Vec3::X)
+            * tuning.terminal_velocity;
+        let from_fast =
+            slide_for(60., overspeed, slope(30.), &tuning);
+        assert!(
+            (from_fast.length() - terminal).abs() < 0.5
+        );
+        // never past the hard cap, however steep
+        let cliff =
+            slide_for(60., Vec3::ZERO, slope(80.), &tuning);
+        assert!(
+            cliff.length()
+                <= tuning.terminal_velocity + 1e-3
+        );
+    }
 
-    for mut linvel in &mut players {
-        let rotated = rotation_matrix * linvel.0;
-        linvel.0 = rotated;
+    #[test]
+    fn friction_stops_riders_on_flat_ground() {
+        let tuning = GameTuning::default();
+        let start = Vec3::NEG_Z * 10.;
+        let mut velocity = start;
+        for _ in 0..(60. / DT) as usize {
+            let next =
+                slide(velocity, Vec3::Y, DT, &tuning);
+            assert!(next.length() <= velocity.length());
+            // slowed down, never pushed back
+            assert!(next.dot(start) >= 0.);
+            velocity = next;
+        }
+        assert_eq!(velocity, Vec3::ZERO);
     }
 }
@@ -0,0 +1,277 @@
+use avian3d::prelude::*;
+use bevy::{color::palettes::tailwind::*, prelude::*};
+
+use crate::{
+    AppState, GameLayer,
+    chain_reaction::Fuse,
+    focus::FocusMeter,
+    playing::Player,
+    scoring::RunStats,
+    split_screen::PlayerSlot,
+    terrain_chunking::Obstacle,
+    time_dilation::{DilationSource, TimeDilation},
+    tuning::GameTuning,
+};
+
+pub struct NearMissPlugin;
+
+impl Plugin for NearMissPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<NearMiss>()
+            .add_systems(
+                FixedUpdate,
+                detect_near_misses
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (
+                    reward_near_misses,
+                    update_near_miss_text,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// The player came within
+/// [`GameTuning::near_miss_distance`] of an
+/// obstacle and got away without touching it.
+#[derive(Event, Debug)]
+pub struct NearMiss {
+    pub player: Entity,
+    pub obstacle: Entity,
+    /// m/s the player was going as they passed
+    pub speed: f32,
+}
+
+/// Obstacles the player is currently passing
+/// close to.
+#[derive(Component, Default, Debug)]
+pub struct NearObstacles(Vec<Entity>);
+
+/// A player's near misses in quick succession,
+/// which are worth more.
+#[derive(Component, Default, Debug)]
+pub struct NearMissCombo {
+    count: u32,
+    /// real seconds left to extend the combo
+    remaining: f32,
+}
+
+#[derive(Component)]
+struct NearMissText;
+
+/// The near miss combo of the player in `slot`,
+/// shown in their `camera`'s viewport.
+pub fn near_miss_hud(
+    slot: usize,
+    camera: Entity,
+    font: Handle<Font>,
+) -> impl Bundle {
+    (
+        StateScoped(AppState::Playing),
+        NearMissText,
+        PlayerSlot(slot),
+        UiTargetCamera(camera),
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(30.),
+            top: Val::Px(30.),
+            ..default()
+        },
+        Text::new(""),
+        TextColor(VIOLET_400.into()),
+        TextFont {
+            font,
+            font_size: 28.,
+            ..default()
+        },
+    )
+}
+
+/// Keeps track of which obstacles are inside the
+/// near miss shell around the player and reports
+/// the ones that leave it still standing.
+fn detect_near_misses(
+    spatial_query: SpatialQuery,
+    mut players: Query<
+        (
+            Entity,
+            &Position,
+            &mut LinearVelocity,
+            &mut NearObstacles,
+        ),
+        With<Player>,
+    >,
+    // obstacles that have been hit are either gone
+    // or burning down
+    standing: Query<(), (With<Obstacle>, Without<Fuse>)>,
+    tuning: Res<GameTuning>,
+    mut near_misses: EventWriter<NearMiss>,
+) {
+    let shell = Collider::sphere(tuning.near_miss_distance);
+    for (player, position, mut linvel, mut near) in
+        &mut players
+    {
+        let inside: Vec<Entity> = spatial_query
+            .shape_intersections(
+                &shell,
+                position.0,
+                Quat::IDENTITY,
+                &SpatialQueryFilter::from_mask(
+                    GameLayer::Default,
+                ),
+            )
+            .into_iter()
+            .filter(|entity| standing.contains(*entity))
+            .collect();
+
+        let speed = linvel.length();
+        for obstacle in near.0.iter().copied() {
+            if inside.contains(&obstacle)
+                || !standing.contains(obstacle)
+                || speed < tuning.near_miss_min_speed
+            {
+                continue;
+            }
+            near_misses.write(NearMiss {
+                player,
+                obstacle,
+                speed,
+            });
+            // boosted here rather than with the other
+            // rewards to keep replays deterministic
+            let boost = linvel.normalize_or_zero()
+                * tuning.near_miss_boost;
+            linvel.0 += boost;
+        }
+        near.0 = inside;
+    }
+}
+
+fn reward_near_misses(
+    mut near_misses: EventReader<NearMiss>,
+    mut combos: Query<&mut NearMissCombo>,
+    mut stats: ResMut<RunStats>,
+    mut meters: Query<&mut FocusMeter>,
+    mut time_dilation: ResMut<TimeDilation>,
+    real_time: Res<Time<Real>>,
+    tuning: Res<GameTuning>,
+) {
+    for mut combo in &mut combos {
+        combo.remaining -= real_time.delta_secs();
+        if combo.remaining <= 0. {
+            combo.count = 0;
+        }
+    }
+
+    for near_miss in near_misses.read() {
+        info!(?near_miss, "near miss");
+        let Ok(mut combo) =
+            combos.get_mut(near_miss.player)
+        else {
+            continue;
+        };
+        combo.count += 1;
+        combo.remaining = tuning.near_miss_combo_window;
+
+        stats.near_misses += 1;
+        stats.near_miss_points +=
+            tuning.near_miss_points * combo.count;
+        if let Ok(mut meter) =
+            meters.get_mut(near_miss.player)
+        {
+            meter.refill(tuning.focus_near_miss_refill);
+        }
+
+        if tuning.near_miss_slowmo_duration > 0. {
+            time_dilation.request(
+                DilationSource::NearMiss,
+                tuning.near_miss_slowmo_scale,
+                Some(tuning.near_miss_slowmo_duration),
+            );
+        }
+    }
+}
+
+fn update_near_miss_text(
+    players: Query<
+        (&NearMissCombo, &PlayerSlot),
+        Changed<NearMissCombo>,
+    >,
+    mut texts: Query<
+        (&mut Text, &PlayerSlot),
+        With<NearMissText>,
+    >,
+) {
+    for (combo, player_slot) in &players {
+        for (mut text, slot) in &mut texts {
+            if slot != player_slot {
+                continue;
+            }
+            text.0 = match combo.count {
+                0 => String::new(),
+                1 => "Near Miss!".to_string(),
+                count => format!("Near Miss x{count}"),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        headless::playtest_app, split_screen::PlayerCount,
+    };
+
+    #[test]
+    fn each_player_keeps_their_own_combo() {
+        let mut app = playtest_app(
+            12345,
+            GameTuning::default(),
+            Duration::from_secs_f64(1. / 64.),
+        );
+        app.insert_resource(PlayerCount(2));
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let mut players = app
+            .world_mut()
+            .query_filtered::<(Entity, &PlayerSlot), With<Player>>();
+        let second = players
+            .iter(app.world())
+            .find(|(_, slot)| slot.0 == 1)
+            .unwrap()
+            .0;
+        for _ in 0..2 {
+            app.world_mut().send_event(NearMiss {
+                player: second,
+                obstacle: Entity::PLACEHOLDER,
+                speed: 50.,
+            });
+            app.update();
+        }
+
+        let mut texts = app.world_mut().query::<(
+            &Text,
+            &PlayerSlot,
+            &UiTargetCamera,
+        )>();
+        let mut texts: Vec<_> = texts
+            .iter(app.world())
+            .filter(|(text, ..)| text.0.starts_with("Near"))
+            .map(|(text, slot, _)| (slot.0, text.0.clone()))
+            .collect();
+        texts.sort();
+        assert_eq!(
+            texts,
+            vec![(1, "Near Miss x2".to_string())]
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use bevy::{
+    color::palettes::tailwind::*, ecs::spawn::SpawnWith,
+    prelude::*, window::WindowFocused,
+};
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    AppState, PauseState,
+    split_screen::{MAX_PLAYERS, PlayerDevice},
+    time_dilation::{DilationSource, TimeDilation},
+    ui::{FONT_PATH, main_menu_text_button},
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_sub_state::<PauseState>()
+            .add_input_context::<PauseControls>()
+            .add_observer(bind_pause)
+            .add_observer(toggle_pause)
+            .add_systems(
+                OnEnter(PauseState::Paused),
+                (freeze_time, spawn_pause_menu),
+            )
+            .add_systems(
+                OnExit(PauseState::Paused),
+                unfreeze_time,
+            )
+            .add_systems(
+                OnEnter(AppState::Restarting),
+                restart,
+            )
+            .add_systems(
+                Update,
+                pause_on_focus_lost
+                    .run_if(in_state(PauseState::Running)),
+            );
+    }
+}
+
+/// Input that's available whether the run is
+/// paused or not.
+#[derive(InputContext)]
+pub struct PauseControls;
+
+#[derive(Debug, InputAction)]
+#[input_action(output = bool)]
+struct Pause;
+
+fn bind_pause(
+    trigger: Trigger<Binding<PauseControls>>,
+    mut actions: Query<&mut Actions<PauseControls>>,
+    devices: Query<&PlayerDevice>,
+) {
+    let mut actions =
+        actions.get_mut(trigger.target()).unwrap();
+    let device = devices
+        .get(trigger.target())
+        .copied()
+        .unwrap_or(PlayerDevice::Any);
+    device.apply_gamepad(&mut actions);
+
+    // anyone on the keyboard can pause
+    device.bind_shared_key::<_, Pause>(
+        &mut actions,
+        KeyCode::KeyP,
+        GamepadButton::Start,
+    );
+}
+
+fn toggle_pause(
+    _trigger: Trigger<Started<Pause>>,
+    state: Option<Res<State<PauseState>>>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    let Some(state) = state else {
+        return;
+    };
+    next_state.set(match state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+    });
+}
+
+fn pause_on_focus_lost(
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<PauseState>>,
+) {
+    if focus_events.read().any(|event| !event.focused) {
+        next_state.set(PauseState::Paused);
+    }
+}
+
+fn freeze_time(mut time_dilation: ResMut<TimeDilation>) {
+    time_dilation.request(DilationSource::Pause, 0., None);
+}
+
+fn unfreeze_time(mut time_dilation: ResMut<TimeDilation>) {
+    time_dilation.clear(DilationSource::Pause);
+}
+
+/// `OnEnter(AppState::Playing)` doesn't run when
+/// going from `Playing` to `Playing`, so restarts
+/// take a one frame detour.
+fn restart(mut next_state: ResMut<NextState<AppState>>) {
+    next_state.set(AppState::Playing);
+}
+
+/// Covers the whole window whatever the split
+/// screen layout, since every player is paused.
+fn spawn_pause_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load(FONT_PATH);
+    let font_resume = font.clone();
+    let font_restart = font.clone();
+    let font_quit = font.clone();
+    // players' cameras may only have part of the
+    // window, so the menu gets one of its own on
+    // top of them all
+    let camera = commands
+        .spawn((
+            StateScoped(PauseState::Paused),
+            Name::new("Pause Menu Camera"),
+            Camera2d,
+            Camera {
+                // share the players' render target
+                hdr: true,
+                order: MAX_PLAYERS as isize,
+                clear_color: ClearColorConfig::None,
+                ..default()
+            },
+        ))
+        .id();
+    commands.spawn((
+        StateScoped(PauseState::Paused),
+        Name::new("Pause Menu"),
+        UiTargetCamera(camera),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            ..default()
+        },
+        BackgroundColor(SLATE_950.with_alpha(0.6).into()),
+        children![(
+            Node {
+                width: Val::Px(250.),
+                height: Val::Auto,
+                margin: UiRect::all(Val::Px(125.)),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::End,
+                ..default()
+            },
+            Children::spawn((
+                Spawn((
+                    Text::new("Paused"),
+                    TextFont {
+                        font,
+                        font_size: 42.,
+                        ..default()
+                    },
+                    TextColor(SLATE_50.into()),
+                )),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Resume",
+                            font_resume,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<PauseState>,
+                            >| {
+                                next_state.set(
+                                    PauseState::Running,
+                                );
+                            },
+                        );
+                }),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Restart",
+                            font_restart,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<AppState>,
+                            >| {
+                                next_state.set(
+                                    AppState::Restarting,
+                                );
+                            },
+                        );
+                }),
+                SpawnWith(|parent: &mut ChildSpawner| {
+                    parent
+                        .spawn(main_menu_text_button(
+                            "Quit to Menu",
+                            font_quit,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<
+                                Pointer<Click>,
+                            >,
+                             mut next_state: ResMut<
+                                NextState<AppState>,
+                            >| {
+                                next_state.set(
+                                    AppState::MainMenu,
+                                );
+                            },
+                        );
+                }),
+            ))
+        )],
+    ));
+}
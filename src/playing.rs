@@ -1,50 +1,62 @@
 use avian3d::prelude::*;
-use bevy::{
-    color::palettes::tailwind::*, pbr::light_consts::lux,
-    prelude::*,
-};
+use bevy::{color::palettes::tailwind::*, prelude::*};
 use bevy_enhanced_input::prelude::*;
-use noiz::prelude::*;
 
 use crate::{
-    AppState,
-    movement::{FastFall, Grounded},
+    AppState, GameLayer, Riding,
+    camera_modes::CameraControls,
+    chain_reaction::Detonate,
+    chase_camera::Trauma,
+    crash::Crashed,
+    focus::{FocusMeter, focus_meter_hud},
+    movement::{
+        ControlInput, ControlInputSet, GroundState,
+        Grounded, JumpCharge, Pitch, Rider, slide,
+    },
+    near_miss::{
+        NearMissCombo, NearObstacles, near_miss_hud,
+    },
+    pause::PauseControls,
+    scoring::RunStats,
+    settings::Settings,
+    split_screen::{
+        PlayerCount, PlayerDevice, PlayerSlot,
+        assign_devices, player_camera,
+    },
     terrain_chunking::{
-        LandChunkNoise, Obstacle, TERRAIN_AMPLITUDE,
+        LandChunkNoise, Obstacle, ObstacleKind,
+        terrain_height,
     },
+    time_dilation::{DilationSource, TimeDilation},
+    tuning::GameTuning,
+    ui::FONT_PATH,
 };
 
 pub struct PlayingPlugin;
 
 impl Plugin for PlayingPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<ShapeCastGrounded>()
-            .init_resource::<HitstopTimer>()
-            .init_resource::<Lives>()
-            .init_resource::<HighSpeed>()
+        app.init_resource::<HighSpeed>()
+            .add_observer(cover_knocked_out_view)
             .add_systems(
                 OnEnter(AppState::Playing),
                 start_playing,
             )
-            .add_systems(
-                OnExit(AppState::Playing),
-                stop_playing,
-            )
             .add_systems(
                 FixedUpdate,
                 (
                     // min_linear,
                     gravity, casting,
                 )
-                    .run_if(in_state(AppState::Playing)),
+                    .after(ControlInputSet)
+                    .run_if(in_state(Riding)),
             )
             .add_systems(
                 Update,
                 (
-                    camera_follow,
-                    debug_side_camera_follow,
                     update_speed_text,
-                    tick_hitstop,
+                    update_lives_text,
+                    flicker_invulnerable,
                 )
                     .run_if(in_state(AppState::Playing)),
             )
@@ -56,7 +68,7 @@ impl Plugin for PlayingPlugin {
     }
 }
 
-#[derive(Resource)]
+#[derive(Component)]
 struct Lives(u32);
 
 impl Default for Lives {
@@ -65,6 +77,28 @@ impl Default for Lives {
     }
 }
 
+#[derive(Component)]
+struct LivesText;
+
+/// A player ran out of lives while others are
+/// still riding, and is about to be despawned.
+#[derive(Event)]
+struct KnockedOut;
+
+/// Obstacle hits don't cost lives until this
+/// finishes.
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn new(tuning: &GameTuning) -> Self {
+        Self(Timer::from_seconds(
+            tuning.invulnerability_duration,
+            TimerMode::Once,
+        ))
+    }
+}
+
 fn update_previous_velocity(
     mut query: Query<(
         &LinearVelocity,
@@ -77,33 +111,14 @@ fn update_previous_velocity(
 }
 
 #[derive(Component)]
+#[require(Rider)]
 pub struct Player;
 
-#[derive(Resource, Default)]
-struct ShapeCastGrounded(bool);
-
 #[derive(Component)]
 struct SpeedText;
 
-#[derive(Component)]
-struct GroundedText;
-
 #[derive(Component, Debug)]
-pub struct LastFrameVelocity(Vec3);
-
-#[derive(Resource)]
-struct HitstopTimer(Timer);
-
-impl Default for HitstopTimer {
-    fn default() -> Self {
-        // 0.02 is the slowdown virtual time
-        // 0.020 is the "realtime" timer time
-        Self(Timer::from_seconds(
-            0.01,
-            TimerMode::Once,
-        ))
-    }
-}
+pub struct LastFrameVelocity(pub Vec3);
 
 fn start_playing(
     mut commands: Commands,
@@ -113,193 +128,282 @@ fn start_playing(
     noise: Res<LandChunkNoise>,
     // time: ResMut<Time<Virtual>>,
     asset_server: Res<AssetServer>,
+    tuning: Res<GameTuning>,
+    mut player_count: ResMut<PlayerCount>,
+    settings: Res<Settings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    main_camera: Single<
+        Entity,
+        (
+            With<PlayerFollowCamera>,
+            With<PlayerSlot>,
+        ),
+    >,
 ) {
     // time.set_relative_speed(0.02);
 
-    // Text with multiple sections
+    info!("start playing");
     commands.spawn((
-        Node{
-            width: Val::Percent(100.),
-            height: Val::Percent(100.),
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::FlexEnd,
+        StateScoped(AppState::Playing),
+        DirectionalLight {
+            shadows_enabled: true,
+            // lux::RAW_SUNLIGHT is recommended for use with
+            // this feature, since other values
+            // approximate sunlight *post-scattering* in
+            // various conditions. RAW_SUNLIGHT
+            // in comparison is the illuminance
+            // of the sun unfiltered by the
+            // atmosphere, so it is the proper input for
+            // sunlight to be filtered by the atmosphere.
+            // illuminance: lux::RAW_SUNLIGHT,
             ..default()
         },
-        children![(
-            Node {
-                padding: UiRect { bottom: Val::Px(50.),..default() },
-                ..default()
-            },
-            Text::new(""),
-            TextColor(SLATE_50.into()),
-            children![
-                (
-                    TextSpan::default(),
-                    TextFont {
-                        // This font is loaded and will be used instead of the default font.
-                        font: asset_server
-                            .load("fonts/Alfa_Slab_One/AlfaSlabOne-Regular.ttf"),
-                        font_size: 42.0,
-                        ..default()
-                    },
-                    SpeedText,
-                ),
-                (
-                    TextSpan::new(" m/s"),
-                    TextFont {
-                        // This font is loaded and will be used instead of the default font.
-                        font: asset_server
-                            .load("fonts/Alfa_Slab_One/AlfaSlabOne-Regular.ttf"),
-                        font_size: 20.0,
-                        ..default()
-                    }
-                )
-            ],
-        )]
     ));
 
-    // Text with multiple sections
-    //     commands.spawn((
-    //     Node{
-    //         width: Val::Percent(100.),
-    //         height: Val::Percent(100.),
-    //         justify_content:
-    // JustifyContent::Center,
-    //         align_items: AlignItems::FlexEnd,
-    //         ..default()
-    //     },
-    //     children![(
-    //         Node {
-    //             padding: UiRect { bottom:
-    // Val::Px(100.),..default() },
-    // ..default()         },
-    //         Text::new("Grounded: "),
-    //         TextColor(SLATE_50.into()),
-    //         TextFont {
-    //             // This font is loaded and will be
-    // used instead of the default font.
-    //             font: asset_server
-    //
-    // .load("fonts/Alfa_Slab_One/AlfaSlabOne-Regular.
-    // ttf"),             font_size: 42.0,
-    //             ..default()
-    //         },
-    //         children![
-    //             (
-    //                 TextSpan::default(),
-    //                 TextFont {
-    //                     // This font is loaded and
-    // will be used instead of the default font.
-    //                     font: asset_server
-    //
-    // .load("fonts/Alfa_Slab_One/AlfaSlabOne-Regular.
-    // ttf"),                     font_size: 42.0,
-    //                     ..default()
-    //                 },
-    //                 GroundedText,
-    //             ),
-    //         ],
-    //     )]
-    // ));
-
-    info!("start playing");
-    commands.spawn(DirectionalLight {
-        shadows_enabled: true,
-        // lux::RAW_SUNLIGHT is recommended for use with
-        // this feature, since other values
-        // approximate sunlight *post-scattering* in various
-        // conditions. RAW_SUNLIGHT in comparison is the
-        // illuminance of the sun unfiltered by the
-        // atmosphere, so it is the proper input for
-        // sunlight to be filtered by the atmosphere.
-        // illuminance: lux::RAW_SUNLIGHT,
-        ..default()
-    });
-
     // for i in 0..40 {
 
     // }
 
-    let player_position: f32 = noise.sample(Vec3::ZERO);
-    // let player_position = 0.;
+    let mesh = meshes.add(Capsule3d::new(0.5, 1.));
+    let gamepads: Vec<Entity> = gamepads.iter().collect();
+    let devices = assign_devices(player_count.0, &gamepads);
+    // a gamepad may have gone since the count was
+    // picked
+    player_count.0 = devices.len();
+    for (slot, device) in
+        devices.iter().copied().enumerate()
+    {
+        let camera = if slot == 0 {
+            *main_camera
+        } else {
+            commands
+                .spawn((
+                    StateScoped(AppState::Playing),
+                    player_camera(
+                        slot,
+                        settings.camera_mode,
+                    ),
+                    TransformInterpolation,
+                ))
+                .id()
+        };
+        spawn_player_hud(
+            &mut commands,
+            &asset_server,
+            slot,
+            camera,
+        );
+
+        // side by side, centered on the middle of the
+        // slope
+        let x = (slot as f32
+            - (devices.len() - 1) as f32 / 2.)
+            * 6.;
+        spawn_player(
+            &mut commands,
+            mesh.clone(),
+            // one each, since invulnerability flickers
+            // the material
+            materials.add(StandardMaterial {
+                base_color: SLATE_400.into(),
+                ..default()
+            }),
+            &tuning,
+            slot,
+            device,
+            Vec3::new(
+                x,
+                terrain_height(&noise, x, 0.) + 1.,
+                0.,
+            ),
+        );
+    }
+}
+
+/// The controller and physics body shared by
+/// every [`Rider`], starting at `position` with
+/// the run's start speed. Collision layers are
+/// left to the caller.
+pub fn rider_body(
+    tuning: &GameTuning,
+    position: Vec3,
+) -> impl Bundle {
+    (
+        (
+            ControlInput::default(),
+            JumpCharge::default(),
+            Pitch::default(),
+            GroundState::default(),
+        ),
+        Transform::from_translation(position),
+        RigidBody::Kinematic,
+        Collider::capsule(0.5, 1.),
+        LockedAxes::ROTATION_LOCKED,
+        LinearVelocity(Vec3::NEG_Z * tuning.start_speed),
+        LastFrameVelocity(Vec3::NEG_Z * tuning.start_speed),
+        CollisionEventsEnabled,
+        ShapeCaster::new(
+            Collider::capsule(0.5, 1.),
+            Vec3::ZERO,
+            Quat::default(),
+            Dir3::NEG_Y,
+        )
+        .with_max_distance(0.2)
+        .with_query_filter(
+            SpatialQueryFilter::from_mask(
+                GameLayer::Default,
+            ),
+        ),
+    )
+}
 
+fn spawn_player(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    tuning: &GameTuning,
+    slot: usize,
+    device: PlayerDevice,
+    position: Vec3,
+) {
     commands
         .spawn((
             Player,
-            Name::new("Player"),
+            Name::new(format!("Player {}", slot + 1)),
+            StateScoped(AppState::Playing),
             TransformInterpolation,
-            Actions::<Grounded>::default(),
-            Mesh3d(meshes.add(Capsule3d::new(0.5, 1.))),
-            MeshMaterial3d(materials.add(
-                StandardMaterial {
-                    base_color: SLATE_400.into(),
-                    ..default()
-                },
-            )),
-            Transform::from_xyz(
-                0.,
-                player_position * TERRAIN_AMPLITUDE + 1.,
-                0.,
+            (
+                PlayerSlot(slot),
+                device,
+                Lives::default(),
+                Actions::<Grounded>::default(),
+                Actions::<PauseControls>::default(),
+                Actions::<CameraControls>::default(),
+                NearObstacles::default(),
+                NearMissCombo::default(),
+                Trauma::default(),
+                FocusMeter::default(),
+            ),
+            Mesh3d(mesh),
+            MeshMaterial3d(material),
+            rider_body(tuning, position),
+            CollisionLayers::new(
+                GameLayer::Player,
+                GameLayer::Default,
             ),
-            RigidBody::Kinematic,
-            Collider::capsule(0.5, 1.),
-            LockedAxes::ROTATION_LOCKED,
-            LinearVelocity(Vec3 {
-                x: 0.,
-                y: 0.,
-                z: -50.,
-            }),
-            LastFrameVelocity(Vec3 {
-                x: 0.,
-                y: 0.,
-                z: -50.,
-            }),
-            CollisionEventsEnabled,
-            ShapeCaster::new(
-                Collider::capsule(0.5, 1.),
-                Vec3::ZERO,
-                Quat::default(),
-                Dir3::NEG_Y,
-            )
-            .with_max_distance(0.2),
         ))
         .observe(
             |trigger: Trigger<OnCollisionStart>,
-             obstacles: Query<&Obstacle>,
-             mut time: ResMut<Time<Virtual>>,
-             mut hitstop_timer: ResMut<HitstopTimer>,
+             obstacles: Query<
+                &ObstacleKind,
+                With<Obstacle>,
+            >,
+             invulnerable: Query<
+                (),
+                Or<(With<Invulnerable>, With<Crashed>)>,
+            >,
+             tuning: Res<GameTuning>,
+             mut time_dilation: ResMut<TimeDilation>,
+             mut stats: ResMut<RunStats>,
              mut commands: Commands,
-             mut velocity: Single<
-                &mut LinearVelocity,
+             mut players: Query<
+                (
+                    &mut LinearVelocity,
+                    &mut Lives,
+                    &mut Trauma,
+                ),
                 With<Player>,
             >,
-             mut lives: ResMut<Lives>,
              mut next_state: ResMut<
                 NextState<AppState>,
             >| {
-                if obstacles.get(trigger.collider).is_ok() {
+                let player_count = players.iter().count();
+                let Ok((
+                    mut velocity,
+                    mut lives,
+                    mut trauma,
+                )) = players.get_mut(trigger.target())
+                else {
+                    return;
+                };
+                if let Ok(kind) =
+                    obstacles.get(trigger.collider)
+                {
                     info!("colliding");
+                    if invulnerable
+                        .contains(trigger.target())
+                    {
+                        // still recovering from the last
+                        // hit, plow straight through
+                        commands.trigger_targets(
+                            Detonate {
+                                velocity: velocity.0,
+                            },
+                            trigger.collider,
+                        );
+                        return;
+                    }
                     // info!(event=?trigger.event());
                     // start with double `Virtual` time
                     // resulting in one of the sprites
                     // moving at twice the speed
                     // of the other sprite which moves based
                     // on `Real` (unscaled) time
-                    time.set_relative_speed(0.02);
-                    *hitstop_timer = Default::default();
-                    commands
-                        .entity(trigger.collider)
-                        .despawn();
+                    time_dilation.request(
+                        DilationSource::Hitstop,
+                        tuning.hitstop_speed,
+                        Some(tuning.hitstop_duration),
+                    );
+                    commands.trigger_targets(
+                        Detonate {
+                            velocity: velocity.0,
+                        },
+                        trigger.collider,
+                    );
 
-                    velocity.0 *= 0.75;
+                    stats.hits += 1;
+
+                    // big enough hits knock the player
+                    // over instead of just slowing them
+                    let severity = velocity.length()
+                        * kind.crash_factor();
+                    velocity.0 *= tuning.collision_slowdown;
+                    trauma.add(
+                        severity / tuning.crash_threshold,
+                    );
+                    // a crash is invulnerable until
+                    // the rider is back on their feet,
+                    // then recovers like any other hit
+                    if severity >= tuning.crash_threshold {
+                        commands
+                            .entity(trigger.target())
+                            .insert(Crashed::new(&tuning));
+                    } else {
+                        commands
+                            .entity(trigger.target())
+                            .insert(Invulnerable::new(
+                                &tuning,
+                            ));
+                    }
                     match lives.0.checked_sub(1) {
                         Some(new_lives) => {
                             lives.0 = new_lives;
                         }
+                        None if player_count > 1 => {
+                            // out of the run, the others
+                            // carry on
+                            commands.trigger_targets(
+                                KnockedOut,
+                                trigger.target(),
+                            );
+                            commands
+                                .entity(trigger.target())
+                                .despawn();
+                        }
                         None => {
                             // game over
                             next_state
-                                .set(AppState::MainMenu);
+                                .set(AppState::GameOver);
                         }
                     }
                 }
@@ -307,94 +411,230 @@ fn start_playing(
         );
 }
 
-fn tick_hitstop(
-    mut hitstop_timer: ResMut<HitstopTimer>,
-    time: Res<Time>,
-    mut virtual_time: ResMut<Time<Virtual>>,
-    // real_time: Res<Time<Real>>
+/// The speed, lives, focus and near misses of the
+/// player in `slot`, shown in their `camera`'s
+/// viewport.
+fn spawn_player_hud(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    slot: usize,
+    camera: Entity,
 ) {
-    if hitstop_timer.0.tick(time.delta()).just_finished() {
-        virtual_time.set_relative_speed(1.);
-    }
-}
+    // Text with multiple sections
+    commands.spawn((
+        StateScoped(AppState::Playing),
+        UiTargetCamera(camera),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::FlexEnd,
+            ..default()
+        },
+        children![(
+            Node {
+                padding: UiRect {
+                    bottom: Val::Px(50.),
+                    ..default()
+                },
+                ..default()
+            },
+            Text::new(""),
+            TextColor(SLATE_50.into()),
+            children![
+                (
+                    TextSpan::default(),
+                    TextFont {
+                        // This font is loaded and will be
+                        // used instead of the default font.
+                        font: asset_server.load(FONT_PATH),
+                        font_size: 42.0,
+                        ..default()
+                    },
+                    SpeedText,
+                    PlayerSlot(slot),
+                ),
+                (
+                    TextSpan::new(" m/s"),
+                    TextFont {
+                        // This font is loaded and will be
+                        // used instead of the default font.
+                        font: asset_server.load(FONT_PATH),
+                        font_size: 20.0,
+                        ..default()
+                    }
+                )
+            ],
+        )],
+    ));
 
-fn stop_playing() {}
+    commands.spawn((
+        StateScoped(AppState::Playing),
+        UiTargetCamera(camera),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(30.),
+            top: Val::Px(30.),
+            ..default()
+        },
+        Text::new("Lives: "),
+        TextColor(SLATE_50.into()),
+        TextFont {
+            font: asset_server.load(FONT_PATH),
+            font_size: 28.0,
+            ..default()
+        },
+        children![(
+            TextSpan::default(),
+            TextFont {
+                font: asset_server.load(FONT_PATH),
+                font_size: 28.0,
+                ..default()
+            },
+            LivesText,
+            PlayerSlot(slot),
+        )],
+    ));
 
-#[derive(Component)]
-pub struct PlayerFollowCamera;
+    commands.spawn(focus_meter_hud(slot, camera));
+    commands.spawn(near_miss_hud(
+        slot,
+        camera,
+        asset_server.load(FONT_PATH),
+    ));
+}
 
-fn camera_follow(
-    mut query: Query<
-        &mut Transform,
-        (
-            With<PlayerFollowCamera>,
-            Without<Player>,
-        ),
+/// Covers a knocked out player's viewport and
+/// hides their HUD, since there's nobody left
+/// there to follow.
+fn cover_knocked_out_view(
+    trigger: Trigger<KnockedOut>,
+    players: Query<&PlayerSlot, With<Player>>,
+    cameras: Query<
+        (Entity, &PlayerSlot),
+        With<PlayerFollowCamera>,
     >,
-    player: Single<&Transform, With<Player>>,
-    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
 ) {
-    for mut transform in &mut query {
-        transform.translation.smooth_nudge(
-            &(player.translation + Vec3::new(0., 3., 4.)),
-            4.,
-            time.delta_secs(),
-        );
-        transform.look_at(player.translation, Vec3::Y);
-    }
+    let Ok(player_slot) = players.get(trigger.target())
+    else {
+        return;
+    };
+    let Some((camera, _)) = cameras
+        .iter()
+        .find(|(_, slot)| *slot == player_slot)
+    else {
+        return;
+    };
+    commands.spawn((
+        StateScoped(AppState::Playing),
+        Name::new(format!(
+            "Player {} Knocked Out",
+            player_slot.0 + 1
+        )),
+        UiTargetCamera(camera),
+        GlobalZIndex(1),
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(SLATE_950.into()),
+        children![(
+            Text::new("Knocked Out"),
+            TextFont {
+                font: asset_server.load(FONT_PATH),
+                font_size: 42.,
+                ..default()
+            },
+            TextColor(SLATE_50.into()),
+        )],
+    ));
 }
 
-#[derive(Component)]
-pub struct PlayerFollowCameraDebug;
-
-fn debug_side_camera_follow(
-    mut query: Query<
-        &mut Transform,
-        (
-            With<PlayerFollowCameraDebug>,
-            Without<Player>,
-        ),
+fn update_lives_text(
+    players: Query<(&Lives, &PlayerSlot), Changed<Lives>>,
+    mut texts: Query<
+        (&mut TextSpan, &PlayerSlot),
+        With<LivesText>,
     >,
-    player: Single<&Transform, With<Player>>,
+) {
+    for (lives, player_slot) in &players {
+        for (mut text, slot) in &mut texts {
+            if slot == player_slot {
+                text.0 = lives.0.to_string();
+            }
+        }
+    }
+}
+
+/// Blinks the player's material while they're
+/// invulnerable and restores it afterwards.
+fn flicker_invulnerable(
+    mut commands: Commands,
+    mut players: Query<(
+        Entity,
+        &mut Invulnerable,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    tuning: Res<GameTuning>,
     time: Res<Time>,
 ) {
-    for mut transform in &mut query {
-        transform.translation.smooth_nudge(
-            &(player.translation + Vec3::new(-10., 0., 0.)),
-            10.,
-            time.delta_secs(),
-        );
-        // .looking_at(Vec3::ZERO, Vec3::Y)
+    for (entity, mut invulnerable, material) in &mut players
+    {
+        let Some(material) = materials.get_mut(material)
+        else {
+            continue;
+        };
+        if invulnerable.0.tick(time.delta()).finished() {
+            material.base_color.set_alpha(1.);
+            material.alpha_mode = AlphaMode::Opaque;
+            commands
+                .entity(entity)
+                .remove::<Invulnerable>();
+            continue;
+        }
+        let visible = (invulnerable.0.elapsed_secs()
+            * tuning.invulnerability_flicker_rate
+            * 2.) as u32
+            % 2
+            == 1;
+        material.base_color.set_alpha(if visible {
+            1.
+        } else {
+            0.2
+        });
+        material.alpha_mode = AlphaMode::Blend;
     }
 }
 
+#[derive(Component)]
+pub struct PlayerFollowCamera;
+
 fn gravity(
     mut query: Query<
         (
             &mut LinearVelocity,
             // &ShapeCaster,
-            &ShapeHits,
-            &Actions<Grounded>,
-            &LastFrameVelocity,
+            &GroundState,
+            &ControlInput,
             // &Transform,
         ),
-        With<Player>,
+        (With<Rider>, Without<Crashed>),
     >,
     time: Res<Time>,
-    mut grounded_text: Query<
-        &mut TextSpan,
-        With<GroundedText>,
-    >,
-    mut shape_cast_grounded: ResMut<ShapeCastGrounded>,
+    tuning: Res<GameTuning>,
     // mut gizmos: Gizmos,
-    mut accumulated_downward_velocity: Local<f32>,
 ) {
     for (
         mut velocity,
         // shape_caster,
-        shape_hits,
-        actions,
-        last_frame_velocity,
+        ground_state,
+        input,
         // transform,
     ) in &mut query
     {
@@ -403,71 +643,22 @@ fn gravity(
         //     transform.translation + velocity.0,
         //     SKY_400,
         // );
-        match shape_hits.iter().next() {
-            Some(shape_hit_data) => {
-                // gizmos.arrow(
-                //     shape_hit_data.point1,
-                //     shape_hit_data.point1
-                //         + shape_hit_data.normal1 * 5.,
-                //     SKY_400
-                // );
-
-                // info!(?shape_hit_data);
-                // don't apply gravity
-                for mut text in &mut grounded_text {
-                    text.0 = "True".to_string();
-                }
-                // TODO: dot(cross(normal,up?))
-                // shape_hit_data.normal1;
-
-                // .normalize()
-                if !shape_cast_grounded.0 {
-                    // info!(?velocity, ?last_frame_velocity);
-                    let tangent = shape_hit_data
-                        .normal1
-                        .cross(Vec3::X);
-                    // let angle =
-                    // shape_hit_data.normal1.dot(velocity.
-                    // 0.normalize());
-                    let angle_2 = tangent.dot(
-                        last_frame_velocity.0.normalize(),
-                    );
-
-                    if angle_2 > 0.99 {
-                        info!("perfect");
-                    } else if angle_2 > 0.98 {
-                        info!("good");
-                    } else if angle_2 > 0.95 {
-                        info!("ok");
-                    } else {
-                        info!("meh");
-                    }
-
-                    // gizmos.arrow(
-                    //     shape_hit_data.point1,
-                    //     shape_hit_data.point1
-                    //         + shape_hit_data.
-                    //           normal1 * 5.,
-                    //     SKY_400
-                    // );
-                    // info!(angle=?angle.
-                    // to_degrees());
-                    // cos(90deg) == 0
-                    // angle.to_degrees()
-                }
-                shape_cast_grounded.0 = true;
+        match ground_state.normal() {
+            Some(normal) => {
+                // don't apply gravity, slide along the
+                // slope instead
+                velocity.0 = slide(
+                    velocity.0,
+                    normal,
+                    time.delta_secs(),
+                    &tuning,
+                );
             }
             None => {
-                for mut text in &mut grounded_text {
-                    text.0 = "False".to_string();
-                }
-                if actions
-                    .state::<FastFall>()
-                    .is_ok_and(|v| v == ActionState::Fired)
-                {
+                if input.fast_fall {
                     // apply gravity
-                    velocity.y -=
-                        9.8 * 7. * time.delta_secs();
+                    velocity.y -= tuning.fast_fall_gravity
+                        * time.delta_secs();
                 } else {
                     // if *accumulated_downward_velocity < 0. {
                     //     velocity.y -=
@@ -475,14 +666,13 @@ fn gravity(
                     // }
                     // apply gravity
                     velocity.y -=
-                        9.8 * 2. * time.delta_secs();
+                        tuning.gravity * time.delta_secs();
 
                     // *accumulated_downward_velocity = 0.;
                 }
-
-                shape_cast_grounded.0 = false;
-
-                continue;
+                velocity.0 = velocity.0.clamp_length_max(
+                    tuning.terminal_velocity,
+                );
             }
         }
     }
@@ -513,7 +703,7 @@ fn casting(
             // &mut Transform,
         ),
         // With<KinematicCharacterController>,
-        With<Player>,
+        (With<Rider>, Without<Crashed>),
     >,
     // mut gizmos: Gizmos,
     time: Res<Time>,
@@ -566,10 +756,10 @@ fn casting(
                 &ShapeCastConfig::from_max_distance(
                     current_velocity_magnitude,
                 ),
-                &SpatialQueryFilter::default()
-                    .with_excluded_entities(
-                        excluded_entities,
-                    ),
+                &SpatialQueryFilter::from_mask(
+                    GameLayer::Default,
+                )
+                .with_excluded_entities(excluded_entities),
             ) else {
                 if i == 0 {
                     slide_accumulation = linear_velocity.0
@@ -683,26 +873,339 @@ pub struct HighSpeed(pub f32);
 pub struct HighSpeedText;
 
 fn update_speed_text(
-    player: Single<&LinearVelocity, With<Player>>,
-    mut speed_texts: Query<&mut TextSpan, With<SpeedText>>,
+    players: Query<
+        (&LinearVelocity, &PlayerSlot),
+        With<Player>,
+    >,
+    mut speed_texts: Query<
+        (&mut TextSpan, &PlayerSlot),
+        With<SpeedText>,
+    >,
     mut high_speed: ResMut<HighSpeed>,
     mut high_speed_texts: Query<
         &mut TextSpan,
         (With<HighSpeedText>, Without<SpeedText>),
     >,
 ) {
-    for mut text in &mut speed_texts {
-        text.0 = (player.length() as u32).to_string();
-    }
-    if player.length() > high_speed.0 {
-        high_speed.0 = player.length();
-        for mut text in &mut high_speed_texts {
-            text.0 = (high_speed.0 as u32).to_string();
+    for (velocity, player_slot) in &players {
+        for (mut text, slot) in &mut speed_texts {
+            if slot == player_slot {
+                text.0 =
+                    (velocity.length() as u32).to_string();
+            }
+        }
+        if velocity.length() > high_speed.0 {
+            high_speed.0 = velocity.length();
+            for mut text in &mut high_speed_texts {
+                text.0 = (high_speed.0 as u32).to_string();
+            }
         }
     }
 }
 
-// fn shape_cast_grounded(mut shape_cast_grounded:
-// ResMut<ShapeCastGrounded>) {
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
 
-// }
+    use super::*;
+    use crate::{
+        headless::playtest_app, scoring::LandingQuality,
+        terrain_chunking::LandChunk,
+    };
+
+    fn riding_app() -> (App, Entity) {
+        let mut app = playtest_app(
+            12345,
+            GameTuning::default(),
+            Duration::from_secs_f64(1. / 64.),
+        );
+        app.finish();
+        app.cleanup();
+        app.update();
+        let player = app
+            .world_mut()
+            .query_filtered::<Entity, With<Player>>()
+            .single(app.world())
+            .unwrap();
+        (app, player)
+    }
+
+    /// Runs `player` into an obstacle far from
+    /// the slope.
+    fn hit(
+        app: &mut App,
+        player: Entity,
+        kind: ObstacleKind,
+    ) {
+        let world = app.world_mut();
+        let obstacle = world
+            .spawn((
+                Obstacle,
+                kind,
+                Transform::from_xyz(0., -1000., 0.),
+            ))
+            .id();
+        world.trigger_targets(
+            OnCollisionStart {
+                collider: obstacle,
+                body: None,
+            },
+            player,
+        );
+        world.flush();
+    }
+
+    /// Too soft a hit to crash the player.
+    fn hit_bush(app: &mut App, player: Entity) {
+        hit(app, player, ObstacleKind::Bush);
+    }
+
+    fn lives(app: &App, player: Entity) -> u32 {
+        app.world().get::<Lives>(player).unwrap().0
+    }
+
+    /// What a run looks like a fixed number of
+    /// updates after it starts.
+    #[derive(Debug, PartialEq)]
+    struct RunSnapshot {
+        entities: u32,
+        players: usize,
+        chunks: usize,
+        obstacles: usize,
+        lives: u32,
+        hits: u32,
+        distance: f32,
+        relative_speed: f32,
+    }
+
+    fn snapshot(app: &mut App) -> RunSnapshot {
+        let world = app.world_mut();
+        let lives: Vec<u32> = world
+            .query_filtered::<&Lives, With<Player>>()
+            .iter(world)
+            .map(|lives| lives.0)
+            .collect();
+        let chunks = world
+            .query_filtered::<(), With<LandChunk>>()
+            .iter(world)
+            .count();
+        let obstacles = world
+            .query_filtered::<(), With<Obstacle>>()
+            .iter(world)
+            .count();
+        let stats = world.resource::<RunStats>();
+        RunSnapshot {
+            entities: world.entities().len(),
+            players: lives.len(),
+            chunks,
+            obstacles,
+            lives: lives.iter().sum(),
+            hits: stats.hits,
+            distance: stats.distance,
+            relative_speed: world
+                .resource::<Time<Virtual>>()
+                .relative_speed(),
+        }
+    }
+
+    #[test]
+    fn restarting_after_game_over_starts_from_scratch() {
+        const UPDATES: usize = 120;
+        let (mut app, _) = riding_app();
+        for _ in 0..UPDATES {
+            app.update();
+        }
+        let baseline = snapshot(&mut app);
+        assert_eq!(baseline.players, 1);
+        assert!(baseline.chunks > 0);
+
+        for _ in 0..3 {
+            // last life, then one more hit
+            let player = app
+                .world_mut()
+                .query_filtered::<Entity, With<Player>>()
+                .single(app.world())
+                .unwrap();
+            app.world_mut()
+                .get_mut::<Lives>(player)
+                .unwrap()
+                .0 = 0;
+            hit_bush(&mut app, player);
+            app.update();
+            assert_eq!(
+                *app.world()
+                    .resource::<State<AppState>>()
+                    .get(),
+                AppState::GameOver
+            );
+
+            // retry
+            app.world_mut()
+                .resource_mut::<NextState<AppState>>()
+                .set(AppState::Playing);
+            app.update();
+            for _ in 0..UPDATES {
+                app.update();
+            }
+            let run = snapshot(&mut app);
+            // distance comes from the interpolated
+            // transform, which depends on where the
+            // frame lands between fixed ticks
+            assert!(
+                (run.distance - baseline.distance).abs()
+                    < 0.1
+            );
+            assert_eq!(
+                RunSnapshot {
+                    distance: baseline.distance,
+                    ..run
+                },
+                baseline
+            );
+        }
+    }
+
+    #[test]
+    fn hits_while_invulnerable_cost_no_lives() {
+        let (mut app, player) = riding_app();
+        let start = lives(&app, player);
+
+        hit_bush(&mut app, player);
+        assert_eq!(lives(&app, player), start - 1);
+        assert!(
+            app.world()
+                .entity(player)
+                .contains::<Invulnerable>()
+        );
+
+        for _ in 0..3 {
+            hit_bush(&mut app, player);
+            app.update();
+        }
+        assert_eq!(lives(&app, player), start - 1);
+    }
+
+    #[test]
+    fn lives_drop_again_once_invulnerability_ends() {
+        let (mut app, player) = riding_app();
+        let start = lives(&app, player);
+
+        hit_bush(&mut app, player);
+        let mut updates = 0;
+        while app
+            .world()
+            .entity(player)
+            .contains::<Invulnerable>()
+        {
+            app.update();
+            updates += 1;
+            assert!(updates < 1000, "never wore off");
+        }
+
+        hit_bush(&mut app, player);
+        assert_eq!(lives(&app, player), start - 2);
+    }
+
+    #[test]
+    fn crashes_stay_invulnerable_until_recovered() {
+        let (mut app, player) = riding_app();
+        let start = lives(&app, player);
+
+        // full speed into a rock knocks them over
+        hit(&mut app, player, ObstacleKind::Rock);
+        assert_eq!(lives(&app, player), start - 1);
+        assert!(
+            !app.world()
+                .entity(player)
+                .contains::<Invulnerable>()
+        );
+        let mut updates = 0;
+        while app
+            .world()
+            .entity(player)
+            .contains::<Crashed>()
+        {
+            hit_bush(&mut app, player);
+            app.update();
+            updates += 1;
+            assert!(updates < 1000, "never got back up");
+        }
+        assert_eq!(lives(&app, player), start - 1);
+
+        // and get the usual grace period once
+        // they're back up
+        assert!(
+            app.world()
+                .entity(player)
+                .contains::<Invulnerable>()
+        );
+        hit_bush(&mut app, player);
+        assert_eq!(lives(&app, player), start - 1);
+    }
+
+    #[test]
+    fn starting_a_run_is_not_a_landing() {
+        let (mut app, _) = riding_app();
+        for _ in 0..10 {
+            app.update();
+        }
+        let stats = app.world().resource::<RunStats>();
+        for quality in LandingQuality::ALL {
+            assert_eq!(stats.landings.count(quality), 0);
+        }
+        // nothing but the distance so far
+        assert_eq!(stats.score(), stats.distance as u32);
+    }
+
+    #[test]
+    fn knocked_out_players_views_are_covered() {
+        let mut app = playtest_app(
+            12345,
+            GameTuning::default(),
+            Duration::from_secs_f64(1. / 64.),
+        );
+        app.insert_resource(PlayerCount(2));
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let (second, slot) = app
+            .world_mut()
+            .query_filtered::<(Entity, &PlayerSlot), With<Player>>()
+            .iter(app.world())
+            .find(|(_, slot)| slot.0 == 1)
+            .map(|(entity, slot)| (entity, *slot))
+            .unwrap();
+        app.world_mut()
+            .get_mut::<Lives>(second)
+            .unwrap()
+            .0 = 0;
+        hit_bush(&mut app, second);
+        app.update();
+
+        assert_eq!(
+            *app.world()
+                .resource::<State<AppState>>()
+                .get(),
+            AppState::Playing
+        );
+        assert!(app.world().get_entity(second).is_err());
+        let camera = app
+            .world_mut()
+            .query_filtered::<(Entity, &PlayerSlot), With<PlayerFollowCamera>>()
+            .iter(app.world())
+            .find(|(_, camera_slot)| **camera_slot == slot)
+            .unwrap()
+            .0;
+        let covered = app
+            .world_mut()
+            .query::<(&UiTargetCamera, &Name)>()
+            .iter(app.world())
+            .any(|(target, name)| {
+                target.0 == camera
+                    && name.as_str()
+                        == "Player 2 Knocked Out"
+            });
+        assert!(covered);
+    }
+}
@@ -14,6 +14,10 @@
 //! some familiarity with rendering concepts and
 //! wgpu.
 
+// the `ShaderType` derive on `PostProcessSettings`
+// generates a size check that's never called
+#![allow(dead_code)]
+
 use bevy::{
     core_pipeline::{
         core_3d::graph::{Core3d, Node3d},
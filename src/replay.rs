@@ -0,0 +1,238 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    AppState,
+    movement::{ControlInput, ControlInputSet},
+    playing::Player,
+    split_screen::single_player,
+    terrain_chunking::TerrainSeed,
+};
+
+/// Bump whenever [`ReplayTick`] or [`Replay`]
+/// change shape. Replays with a different version
+/// are rejected instead of being misinterpreted.
+pub const REPLAY_VERSION: u32 = 3;
+
+#[cfg(not(target_arch = "wasm32"))]
+const LAST_RUN_REPLAY_PATH: &str = "last_run.replay.ron";
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        // `--replay <path>` plays a recorded run back
+        // instead of reading live input.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(path) = std::env::args()
+            .skip_while(|arg| arg != "--replay")
+            .nth(1)
+        {
+            match Replay::load(&path) {
+                Ok(replay) => {
+                    app.insert_resource(ReplayPlayback {
+                        replay,
+                        tick: 0,
+                    });
+                }
+                Err(error) => {
+                    error!(
+                        ?error,
+                        path, "failed to load replay"
+                    );
+                }
+            }
+        }
+
+        app.init_resource::<InputRecorder>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                (start_recording, start_playback),
+            )
+            .add_systems(
+                OnExit(AppState::Playing),
+                save_recording.run_if(single_player),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    play_back_input
+                        .in_set(ControlInputSet)
+                        .run_if(resource_exists::<
+                            ReplayPlayback,
+                        >),
+                    record_input
+                        .after(ControlInputSet)
+                        .run_if(single_player),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// The inputs of a single fixed tick.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct ReplayTick {
+    pub steer: [f32; 2],
+    pub fast_fall: bool,
+    pub jump: bool,
+    pub pitch: f32,
+    pub focus: bool,
+}
+
+impl From<ControlInput> for ReplayTick {
+    fn from(input: ControlInput) -> Self {
+        Self {
+            steer: input.steer.to_array(),
+            fast_fall: input.fast_fall,
+            jump: input.jump,
+            pitch: input.pitch,
+            focus: input.focus,
+        }
+    }
+}
+
+impl From<ReplayTick> for ControlInput {
+    fn from(tick: ReplayTick) -> Self {
+        Self {
+            steer: Vec2::from_array(tick.steer),
+            fast_fall: tick.fast_fall,
+            jump: tick.jump,
+            pitch: tick.pitch,
+            focus: tick.focus,
+        }
+    }
+}
+
+/// Every input of a run, one entry per
+/// `FixedUpdate` tick.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u32,
+    /// seconds per tick
+    pub timestep: f32,
+    pub ticks: Vec<ReplayTick>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Version(u32),
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(ReplayError::Io)?;
+        Self::from_ron(&contents)
+    }
+
+    pub fn from_ron(
+        contents: &str,
+    ) -> Result<Self, ReplayError> {
+        let replay: Replay = ron::from_str(contents)
+            .map_err(ReplayError::Parse)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(
+                replay.version,
+            ));
+        }
+        Ok(replay)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string(self)
+    }
+}
+
+/// Collects the inputs of the run being played.
+#[derive(Resource, Default)]
+pub struct InputRecorder(pub Replay);
+
+/// Feeds a recorded run into [`ControlInput`]
+/// instead of live input.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    pub tick: usize,
+}
+
+fn start_recording(
+    mut recorder: ResMut<InputRecorder>,
+    time: Res<Time<Fixed>>,
+    seed: Res<TerrainSeed>,
+) {
+    recorder.0 = Replay {
+        version: REPLAY_VERSION,
+        seed: seed.0,
+        timestep: time.timestep().as_secs_f32(),
+        ticks: vec![],
+    };
+}
+
+fn start_playback(
+    playback: Option<ResMut<ReplayPlayback>>,
+    mut time: ResMut<Time<Fixed>>,
+    seed: Res<TerrainSeed>,
+) {
+    let Some(mut playback) = playback else {
+        return;
+    };
+    if playback.replay.seed != seed.0 {
+        warn!(
+            replay_seed = playback.replay.seed,
+            seed = seed.0,
+            "replay was recorded on a different seed"
+        );
+    }
+    playback.tick = 0;
+    time.set_timestep(Duration::from_secs_f32(
+        playback.replay.timestep,
+    ));
+}
+
+fn play_back_input(
+    mut playback: ResMut<ReplayPlayback>,
+    mut players: Query<&mut ControlInput, With<Player>>,
+) {
+    let input = playback
+        .replay
+        .ticks
+        .get(playback.tick)
+        .copied()
+        .map(ControlInput::from)
+        .unwrap_or_default();
+    playback.tick += 1;
+    for mut control_input in &mut players {
+        *control_input = input;
+    }
+}
+
+fn record_input(
+    mut recorder: ResMut<InputRecorder>,
+    player: Single<&ControlInput, With<Player>>,
+) {
+    recorder.0.ticks.push((**player).into());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_recording(recorder: Res<InputRecorder>) {
+    let result = recorder
+        .0
+        .to_ron()
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::write(LAST_RUN_REPLAY_PATH, contents)
+                .map_err(|e| e.to_string())
+        });
+    if let Err(error) = result {
+        warn!(?error, "failed to save replay");
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn save_recording() {}
@@ -0,0 +1,460 @@
+use avian3d::prelude::*;
+use bevy::{color::palettes::tailwind::*, prelude::*};
+
+use crate::{
+    AppState, GameLayer,
+    crash::Crashed,
+    movement::{
+        ControlInput, ControlInputSet, GroundState, Rider,
+    },
+    playing::{
+        Invulnerable, Player, PlayerFollowCamera,
+        rider_body,
+    },
+    split_screen::{PlayerSlot, single_player},
+    terrain_chunking::{
+        LandChunkNoise, Obstacle, ObstacleKind,
+        terrain_height,
+    },
+    tuning::GameTuning,
+    ui::FONT_PATH,
+};
+
+pub struct RivalsPlugin;
+
+impl Plugin for RivalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RivalDifficulty>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                spawn_rivals.run_if(single_player),
+            )
+            .add_systems(
+                FixedUpdate,
+                drive_rivals
+                    .in_set(ControlInputSet)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                update_race_position
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// How well the computer riders ride, picked on
+/// the main menu.
+#[derive(
+    Resource, Clone, Copy, Debug, Default, PartialEq, Eq,
+)]
+pub enum RivalDifficulty {
+    /// Solo runs without rivals
+    Off,
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl RivalDifficulty {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RivalDifficulty::Off => "Off",
+            RivalDifficulty::Easy => "Easy",
+            RivalDifficulty::Normal => "Normal",
+            RivalDifficulty::Hard => "Hard",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            RivalDifficulty::Off => RivalDifficulty::Easy,
+            RivalDifficulty::Easy => {
+                RivalDifficulty::Normal
+            }
+            RivalDifficulty::Normal => {
+                RivalDifficulty::Hard
+            }
+            RivalDifficulty::Hard => RivalDifficulty::Off,
+        }
+    }
+
+    /// Meters ahead a rival watches for obstacles
+    fn look_ahead(&self) -> f32 {
+        match self {
+            RivalDifficulty::Off => 0.,
+            RivalDifficulty::Easy => 25.,
+            RivalDifficulty::Normal => 40.,
+            RivalDifficulty::Hard => 60.,
+        }
+    }
+
+    /// How hard a rival pushes the stick to get
+    /// out of the way, from 0 to 1
+    fn avoidance(&self) -> f32 {
+        match self {
+            RivalDifficulty::Off => 0.,
+            RivalDifficulty::Easy => 0.5,
+            RivalDifficulty::Normal => 0.8,
+            RivalDifficulty::Hard => 1.,
+        }
+    }
+
+    /// Seconds a rival charges a jump over
+    /// whatever it can't steer around, 0 never
+    /// jumps
+    fn jump_charge(&self) -> f32 {
+        match self {
+            RivalDifficulty::Off
+            | RivalDifficulty::Easy => 0.,
+            RivalDifficulty::Normal => 0.3,
+            RivalDifficulty::Hard => 0.6,
+        }
+    }
+
+    /// How well a rival lines up its landings,
+    /// from 0 to 1
+    fn pitch_skill(&self) -> f32 {
+        match self {
+            RivalDifficulty::Off
+            | RivalDifficulty::Easy => 0.,
+            RivalDifficulty::Normal => 0.5,
+            RivalDifficulty::Hard => 1.,
+        }
+    }
+}
+
+/// A computer controlled [`Rider`] that fills in
+/// its own [`ControlInput`].
+#[derive(Component, Debug)]
+#[require(Rider)]
+pub struct Rival {
+    difficulty: RivalDifficulty,
+    /// x it drifts back to when nothing's in the
+    /// way
+    lane: f32,
+    /// seconds left holding the current jump
+    jump_hold: f32,
+}
+
+#[derive(Component)]
+struct RacePositionText;
+
+/// Main menu button observer that cycles through
+/// the difficulties.
+pub fn cycle_rival_difficulty(
+    trigger: Trigger<Pointer<Click>>,
+    mut difficulty: ResMut<RivalDifficulty>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+) {
+    *difficulty = difficulty.next();
+    for entity in
+        children.iter_descendants(trigger.target())
+    {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.0 = rival_difficulty_label(*difficulty);
+        }
+    }
+}
+
+pub fn rival_difficulty_label(
+    difficulty: RivalDifficulty,
+) -> String {
+    format!("Rivals: {}", difficulty.label())
+}
+
+/// Lines the rivals up on either side of the
+/// player, a little behind them.
+fn spawn_rivals(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    noise: Res<LandChunkNoise>,
+    difficulty: Res<RivalDifficulty>,
+    tuning: Res<GameTuning>,
+    asset_server: Res<AssetServer>,
+    cameras: Query<
+        (Entity, &PlayerSlot),
+        With<PlayerFollowCamera>,
+    >,
+) {
+    if *difficulty == RivalDifficulty::Off
+        || tuning.rival_count == 0
+    {
+        return;
+    }
+
+    let mesh = meshes.add(Capsule3d::new(0.5, 1.));
+    let colors = [
+        ORANGE_400, SKY_400, PINK_400, LIME_400, AMBER_400,
+    ];
+    for i in 0..tuning.rival_count {
+        // 6, -6, 12, -12, ...
+        let side = if i % 2 == 0 { 1. } else { -1. };
+        let lane = side * 6. * (i / 2 + 1) as f32;
+        let z = 2. + i as f32;
+        commands
+            .spawn((
+                Rival {
+                    difficulty: *difficulty,
+                    lane,
+                    jump_hold: 0.,
+                },
+                Name::new(format!("Rival {}", i + 1)),
+                StateScoped(AppState::Playing),
+                TransformInterpolation,
+                Mesh3d(mesh.clone()),
+                // one each, since invulnerability
+                // flickers the material
+                MeshMaterial3d(materials.add(
+                    StandardMaterial {
+                        base_color:
+                            colors[i % colors.len()].into(),
+                        ..default()
+                    },
+                )),
+                rider_body(
+                    &tuning,
+                    Vec3::new(
+                        lane,
+                        terrain_height(&noise, lane, z)
+                            + 1.,
+                        z,
+                    ),
+                ),
+                CollisionLayers::new(
+                    GameLayer::Rival,
+                    GameLayer::Default,
+                ),
+            ))
+            .observe(rival_hit_obstacle);
+    }
+
+    for (camera, slot) in &cameras {
+        commands.spawn((
+            StateScoped(AppState::Playing),
+            RacePositionText,
+            *slot,
+            UiTargetCamera(camera),
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(30.),
+                top: Val::Px(70.),
+                ..default()
+            },
+            Text::new(""),
+            TextColor(SLATE_50.into()),
+            TextFont {
+                font: asset_server.load(FONT_PATH),
+                font_size: 28.,
+                ..default()
+            },
+        ));
+    }
+}
+
+/// Rivals lose speed and can crash like the
+/// player, but leave the obstacle standing.
+fn rival_hit_obstacle(
+    trigger: Trigger<OnCollisionStart>,
+    obstacles: Query<&ObstacleKind, With<Obstacle>>,
+    mut rivals: Query<
+        &mut LinearVelocity,
+        (
+            With<Rival>,
+            Without<Invulnerable>,
+            Without<Crashed>,
+        ),
+    >,
+    tuning: Res<GameTuning>,
+    mut commands: Commands,
+) {
+    let Ok(kind) = obstacles.get(trigger.collider) else {
+        return;
+    };
+    let Ok(mut velocity) = rivals.get_mut(trigger.target())
+    else {
+        return;
+    };
+    let severity = velocity.length() * kind.crash_factor();
+    velocity.0 *= tuning.collision_slowdown;
+    let mut rival = commands.entity(trigger.target());
+    if severity >= tuning.crash_threshold {
+        rival.insert(Crashed::new(&tuning));
+    } else {
+        rival.insert(Invulnerable::new(&tuning));
+    }
+}
+
+/// Steers each rival around the obstacles ahead
+/// of it, jumps what it can't get around and
+/// lines up its landings, depending on its
+/// difficulty.
+fn drive_rivals(
+    spatial_query: SpatialQuery,
+    mut rivals: Query<
+        (
+            &mut Rival,
+            &mut ControlInput,
+            &Position,
+            &LinearVelocity,
+            &GroundState,
+        ),
+        Without<Crashed>,
+    >,
+    obstacles: Query<(), With<Obstacle>>,
+    time: Res<Time>,
+) {
+    let filter =
+        SpatialQueryFilter::from_mask(GameLayer::Default);
+    for (mut rival, mut input, position, linvel, state) in
+        &mut rivals
+    {
+        *input = ControlInput::default();
+        let Ok(direction) = Dir3::new(linvel.0) else {
+            continue;
+        };
+        let difficulty = rival.difficulty;
+
+        // head downhill, back towards its lane
+        input.steer.x = steer_towards(
+            linvel.0,
+            Vec3::new(
+                rival.lane - position.x,
+                0.,
+                -difficulty.look_ahead(),
+            ),
+        );
+
+        if let Some(hit) = obstacle_ahead(
+            &spatial_query,
+            &obstacles,
+            position.0,
+            direction,
+            difficulty.look_ahead(),
+        ) {
+            input.steer.x =
+                dodge(linvel.0, position.0, hit.point1)
+                    * difficulty.avoidance();
+
+            let close =
+                hit.distance < difficulty.look_ahead() / 3.;
+            if close
+                && state.is_grounded()
+                && rival.jump_hold <= 0.
+            {
+                rival.jump_hold = difficulty.jump_charge();
+            }
+        }
+
+        if rival.jump_hold > 0. {
+            // letting go launches the jump
+            input.jump = true;
+            rival.jump_hold -= time.delta_secs();
+        }
+
+        if !state.is_grounded()
+            && let Some(hit) = spatial_query
+                .cast_ray_predicate(
+                    position.0,
+                    Dir3::NEG_Y,
+                    f32::MAX,
+                    true,
+                    &filter,
+                    &|entity| !obstacles.contains(entity),
+                )
+        {
+            // nose up when coming in steeper than
+            // the slope, down when flatter
+            let mut tangent = hit.normal.cross(Vec3::X);
+            if tangent.dot(*direction) < 0. {
+                tangent = -tangent;
+            }
+            input.pitch = ((tangent.y - direction.y) * 4.)
+                .clamp(-1., 1.)
+                * difficulty.pitch_skill();
+        }
+    }
+}
+
+/// Full stick towards `target`, an offset from
+/// a rider going at `velocity`, easing off as
+/// they line up with it.
+pub fn steer_towards(velocity: Vec3, target: Vec3) -> f32 {
+    let right = velocity.cross(Vec3::Y).normalize_or_zero();
+    (right.dot(target.normalize_or_zero()) * 2.)
+        .clamp(-1., 1.)
+}
+
+/// The closest obstacle a rider at `position`
+/// would run into within `look_ahead` meters
+/// along `direction`, with some room to spare.
+pub fn obstacle_ahead(
+    spatial_query: &SpatialQuery,
+    obstacles: &Query<(), With<Obstacle>>,
+    position: Vec3,
+    direction: Dir3,
+    look_ahead: f32,
+) -> Option<ShapeHitData> {
+    spatial_query.cast_shape_predicate(
+        &Collider::sphere(2.),
+        position,
+        Quat::IDENTITY,
+        direction,
+        &ShapeCastConfig::from_max_distance(look_ahead),
+        &SpatialQueryFilter::from_mask(GameLayer::Default),
+        &|entity| obstacles.contains(entity),
+    )
+}
+
+/// Full stick away from whichever side of a
+/// rider going at `velocity` the `point` is on.
+pub fn dodge(
+    velocity: Vec3,
+    position: Vec3,
+    point: Vec3,
+) -> f32 {
+    let right = velocity.cross(Vec3::Y);
+    -(point - position).dot(right).signum()
+}
+
+fn update_race_position(
+    players: Query<(&Transform, &PlayerSlot), With<Player>>,
+    riders: Query<&Transform, With<Rider>>,
+    mut texts: Query<
+        (&mut Text, &PlayerSlot),
+        With<RacePositionText>,
+    >,
+) {
+    let total = riders.iter().count();
+    for (player, player_slot) in &players {
+        // runs head towards -z
+        let ahead = riders
+            .iter()
+            .filter(|rider| {
+                rider.translation.z < player.translation.z
+            })
+            .count();
+        let position = ordinal(ahead + 1);
+        for (mut text, slot) in &mut texts {
+            if slot == player_slot {
+                text.0 = format!("{position} / {total}");
+            }
+        }
+    }
+}
+
+/// 1st, 2nd, 3rd, 4th, ... 11th, 12th, 13th,
+/// ... 21st
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
@@ -0,0 +1,195 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::{AppState, movement::Landed, playing::Player};
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_event::<LandingRated>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                reset_run_stats,
+            )
+            .add_systems(
+                Update,
+                track_run_stats
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
+                rate_landings
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+/// How well the player's velocity lined up with
+/// the ground when they touched down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LandingQuality {
+    Perfect,
+    Good,
+    Ok,
+    Meh,
+}
+
+impl LandingQuality {
+    /// `alignment` is the dot product of the
+    /// ground tangent and the normalized
+    /// landing velocity.
+    pub fn from_alignment(alignment: f32) -> Self {
+        if alignment > 0.99 {
+            LandingQuality::Perfect
+        } else if alignment > 0.98 {
+            LandingQuality::Good
+        } else if alignment > 0.95 {
+            LandingQuality::Ok
+        } else {
+            LandingQuality::Meh
+        }
+    }
+
+    pub fn points(&self) -> u32 {
+        match self {
+            LandingQuality::Perfect => 500,
+            LandingQuality::Good => 250,
+            LandingQuality::Ok => 100,
+            LandingQuality::Meh => 0,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LandingQuality::Perfect => "Perfect",
+            LandingQuality::Good => "Good",
+            LandingQuality::Ok => "Ok",
+            LandingQuality::Meh => "Meh",
+        }
+    }
+
+    pub const ALL: [LandingQuality; 4] = [
+        LandingQuality::Perfect,
+        LandingQuality::Good,
+        LandingQuality::Ok,
+        LandingQuality::Meh,
+    ];
+}
+
+/// Sent every time a player touches down.
+#[derive(Event, Clone, Copy, Debug)]
+pub struct LandingRated {
+    pub entity: Entity,
+    pub quality: LandingQuality,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LandingCounts([u32; 4]);
+
+impl LandingCounts {
+    pub fn record(&mut self, quality: LandingQuality) {
+        self.0[quality as usize] += 1;
+    }
+
+    pub fn count(&self, quality: LandingQuality) -> u32 {
+        self.0[quality as usize]
+    }
+}
+
+/// Points for each obstacle taken out by a chain
+/// reaction.
+pub const CHAIN_DESTRUCTION_POINTS: u32 = 150;
+
+/// Everything the results screen and leaderboard
+/// need to know about the current run.
+///
+/// Split screen runs are scored as a team: the
+/// distance and top speed are the best of any
+/// player, and everything else is added up across
+/// all of them.
+#[derive(Resource, Clone, Debug, Default)]
+pub struct RunStats {
+    /// meters travelled down the hill by whoever
+    /// got furthest
+    pub distance: f32,
+    /// m/s
+    pub top_speed: f32,
+    pub landings: LandingCounts,
+    /// obstacles the players ran into
+    pub hits: u32,
+    /// obstacles destroyed by other obstacles
+    /// rather than by the player
+    pub chain_destructions: u32,
+    pub near_misses: u32,
+    /// near misses are worth more in a combo, so
+    /// their points are added up as they happen
+    pub near_miss_points: u32,
+}
+
+impl RunStats {
+    /// One point per meter plus landing, chain
+    /// reaction and near miss bonuses.
+    pub fn score(&self) -> u32 {
+        let landing_points: u32 = LandingQuality::ALL
+            .iter()
+            .map(|quality| {
+                quality.points()
+                    * self.landings.count(*quality)
+            })
+            .sum();
+        self.distance as u32
+            + landing_points
+            + self.chain_destructions
+                * CHAIN_DESTRUCTION_POINTS
+            + self.near_miss_points
+    }
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats::default();
+}
+
+fn track_run_stats(
+    players: Query<
+        (&Transform, &LinearVelocity),
+        With<Player>,
+    >,
+    mut stats: ResMut<RunStats>,
+) {
+    for (transform, velocity) in &players {
+        // runs start at z = 0 and head towards -z
+        stats.distance =
+            stats.distance.max(-transform.translation.z);
+        stats.top_speed =
+            stats.top_speed.max(velocity.length());
+    }
+}
+
+fn rate_landings(
+    mut landed: EventReader<Landed>,
+    players: Query<(), With<Player>>,
+    mut stats: ResMut<RunStats>,
+    mut rated: EventWriter<LandingRated>,
+) {
+    for landing in landed.read() {
+        if !players.contains(landing.entity) {
+            continue;
+        }
+        let tangent = landing.normal.cross(Vec3::X);
+        let quality = LandingQuality::from_alignment(
+            tangent.dot(landing.velocity.normalize()),
+        );
+        info!(
+            ?quality,
+            airtime = landing.airtime,
+            "landed"
+        );
+        stats.landings.record(quality);
+        rated.write(LandingRated {
+            entity: landing.entity,
+            quality,
+        });
+    }
+}
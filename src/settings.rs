@@ -0,0 +1,82 @@
+//! Player preferences that outlive a run, kept in
+//! `settings.ron` next to the leaderboard.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::camera_modes::CameraMode;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SETTINGS_PATH: &str = "settings.ron";
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load()).add_systems(
+            Update,
+            save_settings
+                .run_if(resource_changed::<Settings>),
+        );
+    }
+}
+
+/// Missing fields fall back to their defaults, so
+/// older settings files keep loading.
+#[derive(
+    Resource, Clone, Debug, Default, Serialize, Deserialize,
+)]
+#[serde(default)]
+pub struct Settings {
+    /// The view new player cameras start in, the
+    /// last one anybody switched to.
+    pub camera_mode: CameraMode,
+}
+
+impl Settings {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn load() -> Self {
+        let Ok(contents) =
+            std::fs::read_to_string(SETTINGS_PATH)
+        else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|error| {
+            warn!(?error, "failed to parse settings");
+            Self::default()
+        })
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )
+        .map_err(|e| e.to_string())
+        .and_then(|contents| {
+            std::fs::write(SETTINGS_PATH, contents)
+                .map_err(|e| e.to_string())
+        });
+        if let Err(error) = result {
+            warn!(?error, "failed to save settings");
+        }
+    }
+
+    // TODO: localStorage
+    #[cfg(target_arch = "wasm32")]
+    fn save(&self) {}
+}
+
+fn save_settings(settings: Res<Settings>) {
+    // loading counts as a change
+    if settings.is_added() {
+        return;
+    }
+    settings.save();
+}
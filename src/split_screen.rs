@@ -0,0 +1,358 @@
+use bevy::{
+    color::palettes::tailwind::*,
+    core_pipeline::bloom::{
+        Bloom, BloomCompositeMode, BloomPrefilter,
+    },
+    prelude::*,
+    render::camera::Viewport,
+    window::PrimaryWindow,
+};
+use bevy_enhanced_input::prelude::*;
+
+use crate::{
+    camera_modes::{CameraMode, TracksideSpot},
+    chase_camera::ChaseCamera,
+    leaderboard::GameMode,
+    playing::PlayerFollowCamera,
+};
+
+pub const MAX_PLAYERS: usize = 4;
+
+pub struct SplitScreenPlugin;
+
+impl Plugin for SplitScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerCount>()
+            .add_systems(Update, layout_viewports);
+    }
+}
+
+/// How many players the next run starts with,
+/// picked on the main menu.
+#[derive(Resource, Debug)]
+pub struct PlayerCount(pub usize);
+
+impl Default for PlayerCount {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// Which player an entity belongs to, from 0.
+/// Players, their cameras and their HUD all carry
+/// one.
+#[derive(
+    Component, Clone, Copy, Debug, PartialEq, Eq, Hash,
+)]
+pub struct PlayerSlot(pub usize);
+
+/// Where a player's input comes from.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlayerDevice {
+    /// The whole keyboard and every gamepad, for
+    /// single player runs.
+    Any,
+    /// WASD, space, left shift and C
+    KeyboardLeft,
+    /// Arrow keys, enter, right shift and right
+    /// control
+    KeyboardRight,
+    Gamepad(Entity),
+}
+
+impl PlayerDevice {
+    /// Only listens to this device's gamepad.
+    pub fn apply_gamepad<C: InputContext>(
+        self,
+        actions: &mut Actions<C>,
+    ) {
+        if let PlayerDevice::Gamepad(gamepad) = self {
+            actions.set_gamepad(gamepad);
+        }
+    }
+
+    /// Binds `A` to this device's movement stick
+    /// or keys.
+    pub fn bind_stick<C: InputContext, A: InputAction>(
+        self,
+        actions: &mut Actions<C>,
+    ) {
+        let binding = actions.bind::<A>();
+        match self {
+            PlayerDevice::Any => binding.to((
+                Cardinal::wasd_keys(),
+                Axial::left_stick(),
+            )),
+            PlayerDevice::KeyboardLeft => {
+                binding.to(Cardinal::wasd_keys())
+            }
+            PlayerDevice::KeyboardRight => {
+                binding.to(Cardinal::arrow_keys())
+            }
+            PlayerDevice::Gamepad(_) => {
+                binding.to(Axial::left_stick())
+            }
+        }
+        .with_modifiers((
+            DeadZone::default(),
+            SmoothNudge::default(),
+        ));
+    }
+
+    /// Binds `A` to the key for this device's
+    /// half of the keyboard, `[left, right]`,
+    /// or to `button`.
+    pub fn bind_button<C: InputContext, A: InputAction>(
+        self,
+        actions: &mut Actions<C>,
+        [left, right]: [KeyCode; 2],
+        button: GamepadButton,
+    ) {
+        let binding = actions.bind::<A>();
+        match self {
+            PlayerDevice::Any => binding.to((left, button)),
+            PlayerDevice::KeyboardLeft => binding.to(left),
+            PlayerDevice::KeyboardRight => {
+                binding.to(right)
+            }
+            PlayerDevice::Gamepad(_) => binding.to(button),
+        };
+    }
+
+    /// Binds `A` to a `key` that works from
+    /// either half of the keyboard, or to
+    /// `button`.
+    pub fn bind_shared_key<
+        C: InputContext,
+        A: InputAction,
+    >(
+        self,
+        actions: &mut Actions<C>,
+        key: KeyCode,
+        button: GamepadButton,
+    ) {
+        let binding = actions.bind::<A>();
+        match self {
+            PlayerDevice::Any => binding.to((key, button)),
+            PlayerDevice::KeyboardLeft
+            | PlayerDevice::KeyboardRight => {
+                binding.to(key)
+            }
+            PlayerDevice::Gamepad(_) => binding.to(button),
+        };
+    }
+}
+
+/// Hands out input devices for `count` players.
+///
+/// A single player gets everything. Otherwise
+/// each gamepad goes to one player and the
+/// keyboard is split in two for whoever is left,
+/// so there can be fewer devices than players.
+pub fn assign_devices(
+    count: usize,
+    gamepads: &[Entity],
+) -> Vec<PlayerDevice> {
+    if count <= 1 {
+        return vec![PlayerDevice::Any];
+    }
+    gamepads
+        .iter()
+        .map(|gamepad| PlayerDevice::Gamepad(*gamepad))
+        .chain([
+            PlayerDevice::KeyboardLeft,
+            PlayerDevice::KeyboardRight,
+        ])
+        .take(count.min(MAX_PLAYERS))
+        .collect()
+}
+
+/// How many players the connected devices are
+/// enough for: one per gamepad plus both halves
+/// of the keyboard.
+pub fn max_players(gamepads: usize) -> usize {
+    (gamepads + 2).min(MAX_PLAYERS)
+}
+
+/// The camera that follows the player in `slot`,
+/// starting out in `mode`.
+pub fn player_camera(
+    slot: usize,
+    mode: CameraMode,
+) -> impl Bundle {
+    (
+        Name::new(format!("Player {} Camera", slot + 1)),
+        PlayerFollowCamera,
+        mode,
+        ChaseCamera::default(),
+        TracksideSpot::default(),
+        PlayerSlot(slot),
+        Camera3d::default(),
+        Camera {
+            hdr: true,
+            order: slot as isize,
+            ..default()
+        },
+        Bloom {
+            intensity: 0.05,
+            low_frequency_boost: 0.7,
+            low_frequency_boost_curvature: 0.95,
+            high_pass_frequency: 1.0,
+            prefilter: BloomPrefilter {
+                threshold: 0.0,
+                threshold_softness: 0.0,
+            },
+            composite_mode:
+                BloomCompositeMode::EnergyConserving,
+            max_mip_dimension: 512,
+            scale: Vec2::ONE,
+        },
+        Transform::from_xyz(0., 3., 4.)
+            .looking_at(Vec3::ZERO, Vec3::Y),
+        DistanceFog {
+            // color: Color::srgb(0.25, 0.0, 0.25),
+            color: SLATE_950.into(),
+            falloff: FogFalloff::Linear {
+                start: 100.0,
+                end: 300.0,
+            },
+            ..default()
+        },
+    )
+}
+
+/// Main menu button observer that cycles through
+/// 1 to as many players as there are devices for,
+/// see [`max_players`].
+pub fn cycle_player_count(
+    trigger: Trigger<Pointer<Click>>,
+    mut count: ResMut<PlayerCount>,
+    mut mode: ResMut<GameMode>,
+    gamepads: Query<(), With<Gamepad>>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+) {
+    let available = max_players(gamepads.iter().count());
+    count.0 = count.0 % available + 1;
+    *mode = if count.0 > 1 {
+        GameMode::SplitScreen
+    } else {
+        GameMode::Endless
+    };
+    for entity in
+        children.iter_descendants(trigger.target())
+    {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.0 = player_count_label(count.0);
+        }
+    }
+}
+
+pub fn player_count_label(count: usize) -> String {
+    format!("Players: {count}")
+}
+
+/// Run condition for things that only make sense
+/// with a single player, like ghosts and replays.
+pub fn single_player(count: Res<PlayerCount>) -> bool {
+    count.0 <= 1
+}
+
+/// Stacks two players on top of each other and
+/// puts three or four in a grid.
+fn layout_viewports(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut cameras: Query<
+        (&mut Camera, &PlayerSlot),
+        With<PlayerFollowCamera>,
+    >,
+) {
+    let count = cameras.iter().count();
+    let size = window.physical_size();
+    if size.x == 0 || size.y == 0 {
+        // minimized
+        return;
+    }
+    for (mut camera, slot) in &mut cameras {
+        let viewport = match count {
+            0 | 1 => None,
+            2 => {
+                let half = UVec2::new(size.x, size.y / 2);
+                Some(Viewport {
+                    physical_position: UVec2::new(
+                        0,
+                        half.y * slot.0 as u32,
+                    ),
+                    physical_size: half,
+                    ..default()
+                })
+            }
+            _ => {
+                let quarter = size / 2;
+                let cell = UVec2::new(
+                    slot.0 as u32 % 2,
+                    slot.0 as u32 / 2,
+                );
+                Some(Viewport {
+                    physical_position: cell * quarter,
+                    physical_size: quarter,
+                    ..default()
+                })
+            }
+        };
+        // `Viewport` isn't `PartialEq`, and writing
+        // it every frame would trip change detection
+        let unchanged = match (&camera.viewport, &viewport)
+        {
+            (None, None) => true,
+            (Some(current), Some(new)) => {
+                current.physical_position
+                    == new.physical_position
+                    && current.physical_size
+                        == new.physical_size
+            }
+            _ => false,
+        };
+        if !unchanged {
+            camera.viewport = viewport;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_keyboard_splits_between_two_players() {
+        assert_eq!(max_players(0), 2);
+        assert_eq!(max_players(1), 3);
+        assert_eq!(max_players(5), MAX_PLAYERS);
+
+        let devices = assign_devices(4, &[]);
+        assert_eq!(
+            devices,
+            vec![
+                PlayerDevice::KeyboardLeft,
+                PlayerDevice::KeyboardRight
+            ]
+        );
+        assert_eq!(devices.len(), max_players(0));
+    }
+
+    #[test]
+    fn gamepads_are_handed_out_first() {
+        let gamepad = Entity::from_raw(7);
+        assert_eq!(
+            assign_devices(2, &[gamepad]),
+            vec![
+                PlayerDevice::Gamepad(gamepad),
+                PlayerDevice::KeyboardLeft
+            ]
+        );
+        assert_eq!(
+            assign_devices(1, &[gamepad]),
+            vec![PlayerDevice::Any]
+        );
+    }
+}
@@ -14,9 +14,11 @@ use bevy::{
     },
 };
 use noiz::prelude::*;
-use rand::{prelude::Distribution, thread_rng};
+use rand::{
+    Rng, SeedableRng, prelude::Distribution, rngs::StdRng,
+};
 
-use crate::{AppState, playing::Player};
+use crate::{AppState, Riding, movement::Rider};
 
 const CHUNK_SIZE: f32 = 200.;
 pub const TERRAIN_AMPLITUDE: f32 = 20.;
@@ -25,28 +27,29 @@ pub struct LandChunkPlugin;
 
 impl Plugin for LandChunkPlugin {
     fn build(&self, app: &mut App) {
-        let mut perlin_noise = Noise::<(
-            // mixes gradients from `QuickGradients` (a
-            // lookup table) across each cell
-            // via a smoothstep, where each
-            // cell is on an orthogonal (cartesian) grid,
-            MixCellGradients<
-                OrthoGrid,
-                Smoothstep,
-                QuickGradients,
-            >,
-            // then maps those snorm values to unorm.
-            SNormToUNorm,
-        )>::default();
-        perlin_noise.set_seed(12345); // Any seed will do. Even 0 is fine!
-
-        app.insert_resource(LandChunkNoise(perlin_noise))
+        let seed = TerrainSeed::default();
+
+        app.insert_resource(LandChunkNoise::new(seed))
+            .insert_resource(seed)
             .init_resource::<LoadedChunks>()
             .add_systems(Startup, gen_debug_material)
             .add_systems(
                 Update,
-                ensure_land_chunks
-                    .run_if(in_state(AppState::Playing)),
+                reseed_terrain.run_if(
+                    resource_changed::<TerrainSeed>,
+                ),
+            )
+            .add_systems(
+                Update,
+                ensure_land_chunks.run_if(in_state(Riding)),
+            )
+            .add_systems(
+                OnExit(AppState::Playing),
+                forget_loaded_chunks,
+            )
+            .add_systems(
+                OnExit(AppState::MainMenu),
+                forget_loaded_chunks,
             );
     }
 }
@@ -72,6 +75,65 @@ fn gen_debug_material(
 #[derive(Component)]
 pub struct LandChunk;
 
+/// The seed the terrain noise was generated from,
+/// picked on the main menu.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct TerrainSeed(pub u32);
+
+impl Default for TerrainSeed {
+    fn default() -> Self {
+        Self(12345)
+    }
+}
+
+/// Main menu button observer that steps to the
+/// next seed, or the previous one on right click.
+pub fn cycle_terrain_seed(
+    trigger: Trigger<Pointer<Click>>,
+    mut seed: ResMut<TerrainSeed>,
+    children: Query<&Children>,
+    mut texts: Query<&mut Text>,
+) {
+    seed.0 = if trigger.button == PointerButton::Secondary {
+        seed.0.wrapping_sub(1)
+    } else {
+        seed.0.wrapping_add(1)
+    };
+    for entity in
+        children.iter_descendants(trigger.target())
+    {
+        if let Ok(mut text) = texts.get_mut(entity) {
+            text.0 = terrain_seed_label(*seed);
+        }
+    }
+}
+
+pub fn terrain_seed_label(seed: TerrainSeed) -> String {
+    format!("Seed: {}", seed.0)
+}
+
+/// Regenerates the noise for a new seed and drops
+/// the terrain built from the old one, which
+/// streams back in around the riders.
+fn reseed_terrain(
+    seed: Res<TerrainSeed>,
+    mut commands: Commands,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    terrain: Query<
+        Entity,
+        Or<(With<LandChunk>, With<Obstacle>)>,
+    >,
+) {
+    if seed.is_added() {
+        return;
+    }
+    commands.insert_resource(LandChunkNoise::new(*seed));
+    loaded_chunks.0.clear();
+    for entity in &terrain {
+        commands.entity(entity).despawn();
+    }
+}
+
 #[derive(Resource, Deref, DerefMut)]
 pub struct LandChunkNoise(
     Noise<(
@@ -89,14 +151,68 @@ pub struct LandChunkNoise(
     )>,
 );
 
+impl LandChunkNoise {
+    pub fn new(seed: TerrainSeed) -> Self {
+        let mut perlin_noise = Noise::default();
+        perlin_noise.set_seed(seed.0); // Any seed will do. Even 0 is fine!
+        Self(perlin_noise)
+    }
+}
+
 #[derive(Resource, Default)]
 struct LoadedChunks(HashMap<u32, Entity>);
 
 #[derive(Component)]
 pub struct Obstacle;
 
+/// What an [`Obstacle`] is, which decides how it
+/// looks and how hard it is to hit.
+#[derive(Component, Clone, Copy, Debug)]
+pub enum ObstacleKind {
+    Rock,
+    Bush,
+}
+
+impl ObstacleKind {
+    pub fn size(&self) -> f32 {
+        match self {
+            ObstacleKind::Rock => 30.,
+            ObstacleKind::Bush => 8.,
+        }
+    }
+
+    pub fn color(&self) -> Srgba {
+        match self {
+            ObstacleKind::Rock => RED_400,
+            ObstacleKind::Bush => GREEN_600,
+        }
+    }
+
+    /// Multiplies the impact speed to get the
+    /// severity of a hit, see
+    /// [`GameTuning::crash_threshold`](crate::tuning::GameTuning::crash_threshold).
+    pub fn crash_factor(&self) -> f32 {
+        match self {
+            ObstacleKind::Rock => 1.,
+            ObstacleKind::Bush => 0.3,
+        }
+    }
+}
+
+/// Height of the terrain surface at `x`, `z` in
+/// world space.
+pub fn terrain_height(
+    noise: &LandChunkNoise,
+    x: f32,
+    z: f32,
+) -> f32 {
+    let height: f32 =
+        noise.sample(Vec3::new(x / 80., 0., z / 80.));
+    height * TERRAIN_AMPLITUDE
+}
+
 fn ensure_land_chunks(
-    query: Single<&Transform, With<Player>>,
+    riders: Query<&Transform, With<Rider>>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     // images: ResMut<Assets<Image>>,
@@ -104,11 +220,23 @@ fn ensure_land_chunks(
     noise: Res<LandChunkNoise>,
     debug_material: Res<DebugMaterial>,
     mut loaded_chunks: ResMut<LoadedChunks>,
+    seed: Res<TerrainSeed>,
+    state: Res<State<AppState>>,
 ) {
-    let offset =
-        (query.translation.z / CHUNK_SIZE).abs() as u32;
-    // info!(?offset);
-    for offset in offset..(offset + 5) {
+    // every rider needs the slope ahead of them
+    let mut offsets: Vec<u32> = riders
+        .iter()
+        .flat_map(|transform| {
+            let offset = (transform.translation.z
+                / CHUNK_SIZE)
+                .abs() as u32;
+            // info!(?offset);
+            offset..(offset + 5)
+        })
+        .collect();
+    offsets.sort_unstable();
+    offsets.dedup();
+    for offset in offsets {
         if loaded_chunks.0.get(&offset).is_none() {
             let chunk = gen_land_chunk(
                 CHUNK_SIZE * offset as f32,
@@ -119,15 +247,22 @@ fn ensure_land_chunks(
             let sampler =
                 UniformMeshSampler::try_new(triangles)
                     .unwrap();
-            let rng = thread_rng();
-            // let rng = StdRng::;
-            let samples: Vec<Vec3> =
-                sampler.sample_iter(rng).take(2).collect();
+            // seeded per chunk so obstacle placement
+            // is the same every time a seed is played
+            let mut rng = StdRng::seed_from_u64(
+                (u64::from(seed.0) << 32)
+                    | u64::from(offset),
+            );
+            let samples: Vec<Vec3> = sampler
+                .sample_iter(&mut rng)
+                .take(2)
+                .collect();
 
             let id = commands
                 .spawn((
                     Name::new("LandChunk"),
                     LandChunk,
+                    StateScoped(state.get().clone()),
                     Mesh3d(meshes.add(chunk)),
                     MeshMaterial3d(
                         debug_material.0.clone(),
@@ -147,6 +282,12 @@ fn ensure_land_chunks(
             loaded_chunks.0.insert(offset, id);
 
             for sample in samples {
+                let kind = if rng.gen_bool(0.5) {
+                    ObstacleKind::Rock
+                } else {
+                    ObstacleKind::Bush
+                };
+                let size = kind.size();
                 info!(
                     ?sample,
                     z = sample.z
@@ -155,16 +296,17 @@ fn ensure_land_chunks(
                 commands.spawn((
                     Name::new("Obstacle"),
                     Obstacle,
-                    Collider::cuboid(30., 30., 30.),
+                    kind,
+                    StateScoped(state.get().clone()),
+                    Collider::cuboid(size, size, size),
                     RigidBody::Static,
                     Mesh3d(
-                        meshes.add(Cuboid::new(
-                            30., 30., 30.,
-                        )),
+                        meshes
+                            .add(Cuboid::from_length(size)),
                     ),
                     MeshMaterial3d(materials.add(
                         StandardMaterial {
-                            base_color: RED_400.into(),
+                            base_color: kind.color().into(),
                             ..default()
                         },
                     )),
@@ -180,6 +322,15 @@ fn ensure_land_chunks(
     }
 }
 
+/// Chunks are despawned with the run or the
+/// attract mode, so whatever comes next has to
+/// generate them again.
+fn forget_loaded_chunks(
+    mut loaded_chunks: ResMut<LoadedChunks>,
+) {
+    loaded_chunks.0.clear();
+}
+
 fn gen_land_chunk(
     offset: f32,
     noise: &LandChunkNoise,
@@ -205,14 +356,10 @@ fn gen_land_chunk(
     };
 
     pos_attribute.iter_mut().for_each(|arr| {
-        let some_value: f32 = noise.sample(Vec3::new(
-            arr[0] / 80.,
-            arr[1] / 10.,
-            (arr[2] - offset) / 80.,
-        ));
         // let some_value =
         //     ((arr[2] - offset) / 10.).sin() * 1.;
-        arr[1] = some_value * TERRAIN_AMPLITUDE;
+        arr[1] =
+            terrain_height(noise, arr[0], arr[2] - offset);
     });
 
     // TODO: Maybe switch to heightfield
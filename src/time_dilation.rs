@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::AppState;
+
+pub struct TimeDilationPlugin;
+
+impl Plugin for TimeDilationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeDilation>()
+            .add_systems(PreUpdate, apply_time_dilation)
+            .add_systems(
+                OnExit(AppState::Playing),
+                clear_time_dilation,
+            );
+    }
+}
+
+/// Who is asking for time to run at a different
+/// speed. Each source has at most one request,
+/// and later variants win over earlier ones.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord,
+)]
+pub enum DilationSource {
+    /// the brief slow down after a near miss,
+    /// which focus can keep going slower
+    NearMiss,
+    SlowMotion,
+    Hitstop,
+    Cinematic,
+    Pause,
+}
+
+#[derive(Clone, Debug)]
+struct DilationRequest {
+    source: DilationSource,
+    scale: f32,
+    /// real time left, `None` lasts until cleared
+    remaining: Option<Duration>,
+}
+
+/// Resolves every active time scale request into
+/// the relative speed of `Time<Virtual>`.
+///
+/// Requests count down in real time, so a
+/// request's duration doesn't depend on how slow
+/// it makes everything else. The highest priority
+/// [`DilationSource`] decides the scale, so a
+/// cinematic can bring time back up to speed
+/// during slow motion, and the slowest request
+/// wins between equals.
+#[derive(Resource, Default, Debug)]
+pub struct TimeDilation {
+    requests: Vec<DilationRequest>,
+}
+
+impl TimeDilation {
+    /// Runs virtual time at `scale` until
+    /// `duration` seconds of real time have
+    /// passed, or until cleared when
+    /// `duration` is `None`. Replaces the
+    /// previous request from the same
+    /// `source`.
+    pub fn request(
+        &mut self,
+        source: DilationSource,
+        scale: f32,
+        duration: Option<f32>,
+    ) {
+        self.clear(source);
+        self.requests.push(DilationRequest {
+            source,
+            scale: scale.max(0.),
+            remaining: duration
+                .map(Duration::from_secs_f32),
+        });
+    }
+
+    pub fn clear(&mut self, source: DilationSource) {
+        self.requests
+            .retain(|request| request.source != source);
+    }
+
+    pub fn clear_all(&mut self) {
+        self.requests.clear();
+    }
+
+    pub fn is_active(
+        &self,
+        source: DilationSource,
+    ) -> bool {
+        self.requests
+            .iter()
+            .any(|request| request.source == source)
+    }
+
+    /// Counts down every request by `delta` of
+    /// real time and drops the ones that ran
+    /// out.
+    pub fn tick(&mut self, delta: Duration) {
+        self.requests.retain_mut(|request| {
+            match &mut request.remaining {
+                Some(remaining) => {
+                    *remaining =
+                        remaining.saturating_sub(delta);
+                    !remaining.is_zero()
+                }
+                None => true,
+            }
+        });
+    }
+
+    /// The relative speed virtual time should run
+    /// at right now.
+    pub fn scale(&self) -> f32 {
+        self.requests
+            .iter()
+            .max_by(|a, b| {
+                a.source
+                    .cmp(&b.source)
+                    .then(b.scale.total_cmp(&a.scale))
+            })
+            .map_or(1., |request| request.scale)
+    }
+}
+
+fn apply_time_dilation(
+    mut dilation: ResMut<TimeDilation>,
+    real_time: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    dilation.tick(real_time.delta());
+    let scale = dilation.scale();
+    if scale == 0. {
+        time.pause();
+    } else {
+        time.unpause();
+        time.set_relative_speed(scale);
+    }
+}
+
+/// Nothing a run asked for outlives it.
+fn clear_time_dilation(mut dilation: ResMut<TimeDilation>) {
+    dilation.clear_all();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seconds(seconds: f32) -> Duration {
+        Duration::from_secs_f32(seconds)
+    }
+
+    #[test]
+    fn the_highest_priority_request_wins() {
+        let mut dilation = TimeDilation::default();
+        assert_eq!(dilation.scale(), 1.);
+
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.3,
+            None,
+        );
+        assert_eq!(dilation.scale(), 0.3);
+        dilation.request(
+            DilationSource::Hitstop,
+            0.05,
+            Some(0.1),
+        );
+        assert_eq!(dilation.scale(), 0.05);
+        dilation.request(DilationSource::Pause, 0., None);
+        assert_eq!(dilation.scale(), 0.);
+    }
+
+    #[test]
+    fn cinematics_can_run_at_full_speed_during_slow_motion()
+    {
+        let mut dilation = TimeDilation::default();
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.3,
+            None,
+        );
+        dilation.request(
+            DilationSource::Hitstop,
+            0.05,
+            Some(0.1),
+        );
+        dilation.request(
+            DilationSource::Cinematic,
+            1.,
+            Some(1.),
+        );
+        assert_eq!(dilation.scale(), 1.);
+
+        // and once it's over, slow motion carries on
+        dilation.tick(seconds(1.));
+        assert_eq!(dilation.scale(), 0.3);
+    }
+
+    #[test]
+    fn requests_replace_their_own_source() {
+        let mut dilation = TimeDilation::default();
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.2,
+            None,
+        );
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.6,
+            None,
+        );
+        assert_eq!(dilation.scale(), 0.6);
+    }
+
+    #[test]
+    fn requests_expire_in_real_time() {
+        let mut dilation = TimeDilation::default();
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.3,
+            None,
+        );
+        dilation.request(
+            DilationSource::Hitstop,
+            0.05,
+            Some(0.2),
+        );
+
+        dilation.tick(seconds(0.15));
+        assert_eq!(dilation.scale(), 0.05);
+        // however slow it made virtual time, it's
+        // over after 0.2 real seconds
+        dilation.tick(seconds(0.05));
+        assert!(
+            !dilation.is_active(DilationSource::Hitstop)
+        );
+        assert_eq!(dilation.scale(), 0.3);
+
+        // open ended requests never run out
+        dilation.tick(seconds(1000.));
+        assert_eq!(dilation.scale(), 0.3);
+    }
+
+    #[test]
+    fn clearing_drops_only_that_source() {
+        let mut dilation = TimeDilation::default();
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.3,
+            None,
+        );
+        dilation.request(DilationSource::Pause, 0., None);
+
+        dilation.clear(DilationSource::Pause);
+        assert_eq!(dilation.scale(), 0.3);
+        assert!(
+            dilation.is_active(DilationSource::SlowMotion)
+        );
+
+        dilation.request(DilationSource::Pause, 0., None);
+        dilation.clear_all();
+        assert_eq!(dilation.scale(), 1.);
+    }
+
+    #[test]
+    fn near_misses_neither_cut_focus_short_nor_block_hitstop()
+     {
+        let mut dilation = TimeDilation::default();
+        dilation.request(
+            DilationSource::SlowMotion,
+            0.3,
+            None,
+        );
+        dilation.request(
+            DilationSource::NearMiss,
+            0.5,
+            Some(0.2),
+        );
+        assert_eq!(dilation.scale(), 0.3);
+        dilation.request(
+            DilationSource::Hitstop,
+            0.05,
+            Some(0.1),
+        );
+        assert_eq!(dilation.scale(), 0.05);
+
+        dilation.tick(seconds(0.3));
+        assert_eq!(dilation.scale(), 0.3);
+    }
+}
@@ -0,0 +1,321 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+const TUNING_PATH: &str = "game.tuning.ron";
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameTuning>()
+            .init_asset_loader::<GameTuningLoader>()
+            .init_resource::<GameTuning>()
+            .add_systems(Startup, load_tuning)
+            .add_systems(PreUpdate, apply_tuning);
+    }
+}
+
+/// Gameplay feel constants, loaded from
+/// `assets/game.tuning.ron` and reloaded whenever
+/// the file changes.
+///
+/// Systems read the [`GameTuning`] resource,
+/// which is kept in sync with the asset. Fields
+/// missing from the file fall back to their
+/// defaults.
+#[derive(
+    Asset,
+    Resource,
+    TypePath,
+    Clone,
+    Debug,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct GameTuning {
+    /// m/s² pulling the rider down
+    pub gravity: f32,
+    /// m/s² while airborne and holding `FastFall`
+    pub fast_fall_gravity: f32,
+    /// m/s² of sideways acceleration the edges
+    /// hold at full steering. Limits the turn
+    /// rate at high speeds, so turns get
+    /// wider the faster the rider goes.
+    pub carve_grip: f32,
+    /// tightest turn, in meters, at full
+    /// steering. Limits the turn rate at low
+    /// speeds.
+    pub min_turn_radius: f32,
+    /// fraction of speed lost per second while
+    /// carving at full steering
+    pub carve_speed_loss: f32,
+    /// how much of the ground steering is
+    /// available in the air
+    pub air_steering: f32,
+    /// m/s added along the ground normal by an
+    /// uncharged jump
+    pub jump_min_speed: f32,
+    /// m/s added along the ground normal by a
+    /// fully charged jump
+    pub jump_max_speed: f32,
+    /// seconds of holding `Jump` to fully charge
+    /// it
+    pub jump_max_charge: f32,
+    /// radians per second the player tilts at
+    /// full pitch input
+    pub pitch_rate: f32,
+    /// furthest the player can tilt either way,
+    /// in radians
+    pub max_pitch: f32,
+    /// radians per second the trajectory bends at
+    /// full pitch input
+    pub pitch_velocity_bend: f32,
+    /// deceleration, in m/s² per (m/s)², from air
+    /// resistance while grounded
+    pub drag: f32,
+    /// coefficient of friction between the rider
+    /// and the snow
+    pub friction: f32,
+    /// m/s the rider can never go faster than
+    pub terminal_velocity: f32,
+    /// impact severity, in m/s scaled by the
+    /// obstacle's crash factor, that knocks the
+    /// player over
+    pub crash_threshold: f32,
+    /// seconds spent tumbling after a crash
+    pub crash_duration: f32,
+    /// m/s down the hill after getting back up
+    pub crash_recovery_speed: f32,
+    /// meters around a destroyed obstacle that
+    /// other obstacles get knocked over in
+    pub chain_radius: f32,
+    /// m/s an obstacle right next to a destroyed
+    /// one gets knocked away with
+    pub chain_kick: f32,
+    /// seconds a knocked over obstacle takes to
+    /// go off in turn
+    pub chain_fuse_duration: f32,
+    /// m/s debris flies apart with, on top of the
+    /// velocity it inherits
+    pub debris_spread: f32,
+    /// seconds before a piece of debris despawns
+    pub debris_lifetime: f32,
+    /// meters from the player past which debris
+    /// despawns early
+    pub debris_max_distance: f32,
+    /// most debris pieces alive at once, the
+    /// oldest make room for new ones
+    pub max_debris: usize,
+    /// relative time speed while focusing
+    pub focus_time_scale: f32,
+    /// meter drained per real second of focusing,
+    /// the meter holds 1
+    pub focus_drain_rate: f32,
+    /// meter refilled by a perfect landing,
+    /// lesser landings refill in proportion
+    /// to their points
+    pub focus_landing_refill: f32,
+    /// meters from an obstacle that count as a
+    /// near miss
+    pub near_miss_distance: f32,
+    /// m/s the player has to be going for a near
+    /// miss to count
+    pub near_miss_min_speed: f32,
+    /// points for a near miss, multiplied by the
+    /// combo count
+    pub near_miss_points: u32,
+    /// real seconds after a near miss in which
+    /// the next one extends the combo
+    pub near_miss_combo_window: f32,
+    /// m/s added along the direction of travel by
+    /// a near miss
+    pub near_miss_boost: f32,
+    /// focus meter refilled by a near miss
+    pub focus_near_miss_refill: f32,
+    /// relative time speed right after a near
+    /// miss
+    pub near_miss_slowmo_scale: f32,
+    /// real seconds of slow motion after a near
+    /// miss, 0 turns it off
+    pub near_miss_slowmo_duration: f32,
+    /// computer riders racing solo runs, see
+    /// [`RivalDifficulty`](crate::rivals::RivalDifficulty)
+    pub rival_count: usize,
+    /// m/s down the hill when a run starts
+    pub start_speed: f32,
+    /// fraction of velocity kept after hitting an
+    /// obstacle
+    pub collision_slowdown: f32,
+    /// how long hitstop lasts, in real seconds
+    pub hitstop_duration: f32,
+    /// relative virtual time speed during hitstop
+    pub hitstop_speed: f32,
+    /// seconds after a hit before the next one
+    /// can cost a life
+    pub invulnerability_duration: f32,
+    /// on/off cycles per second while
+    /// invulnerable
+    pub invulnerability_flicker_rate: f32,
+    /// where the follow camera sits relative to
+    /// the player: x to their right, y up and z
+    /// behind them along their heading
+    pub camera_offset: Vec3,
+    /// how quickly the camera catches up with
+    /// the player, higher is stiffer
+    pub camera_stiffness: f32,
+    /// seconds of the player's velocity the
+    /// camera looks ahead of them
+    pub camera_look_ahead: f32,
+    /// extra meters behind the player per m/s
+    pub camera_pullback: f32,
+    /// vertical field of view standing still, in
+    /// radians
+    pub camera_fov: f32,
+    /// vertical field of view at
+    /// `camera_fov_speed`, in radians
+    pub camera_max_fov: f32,
+    /// m/s at which the field of view is widest
+    pub camera_fov_speed: f32,
+    /// meters the camera keeps above the terrain
+    pub camera_min_clearance: f32,
+    /// meters the camera moves at full trauma
+    pub camera_shake_offset: f32,
+    /// radians the camera rolls at full trauma
+    pub camera_shake_roll: f32,
+    /// trauma lost per real second
+    pub camera_trauma_decay: f32,
+    /// m/s downwards into a landing that shakes
+    /// the camera as hard as a crash
+    pub camera_hard_landing_speed: f32,
+    /// meters down the hill a trackside camera is
+    /// put up ahead of the player
+    pub trackside_lead: f32,
+    /// meters to the side of the player's line a
+    /// trackside camera stands
+    pub trackside_offset: f32,
+}
+
+impl Default for GameTuning {
+    fn default() -> Self {
+        Self {
+            gravity: 9.8 * 2.,
+            fast_fall_gravity: 9.8 * 7.,
+            carve_grip: 26.,
+            min_turn_radius: 40.,
+            carve_speed_loss: 0.15,
+            air_steering: 0.3,
+            jump_min_speed: 4.,
+            jump_max_speed: 12.,
+            jump_max_charge: 0.6,
+            pitch_rate: PI,
+            max_pitch: PI / 3.,
+            pitch_velocity_bend: 0.35,
+            drag: 0.002,
+            friction: 0.03,
+            terminal_velocity: 90.,
+            crash_threshold: 45.,
+            crash_duration: 2.,
+            crash_recovery_speed: 20.,
+            chain_radius: 80.,
+            chain_kick: 30.,
+            chain_fuse_duration: 0.6,
+            debris_spread: 8.,
+            debris_lifetime: 6.,
+            debris_max_distance: 300.,
+            max_debris: 64,
+            focus_time_scale: 0.35,
+            focus_drain_rate: 0.25,
+            focus_landing_refill: 0.25,
+            near_miss_distance: 4.,
+            near_miss_min_speed: 20.,
+            near_miss_points: 100,
+            near_miss_combo_window: 3.,
+            near_miss_boost: 3.,
+            focus_near_miss_refill: 0.1,
+            near_miss_slowmo_scale: 0.5,
+            near_miss_slowmo_duration: 0.2,
+            rival_count: 3,
+            start_speed: 50.,
+            collision_slowdown: 0.75,
+            hitstop_duration: 0.15,
+            hitstop_speed: 0.02,
+            invulnerability_duration: 1.5,
+            invulnerability_flicker_rate: 8.,
+            camera_offset: Vec3::new(0., 3., 4.),
+            camera_stiffness: 4.,
+            camera_look_ahead: 0.25,
+            camera_pullback: 0.04,
+            camera_fov: PI / 4.,
+            camera_max_fov: PI / 2.75,
+            camera_fov_speed: 90.,
+            camera_min_clearance: 1.,
+            camera_shake_offset: 0.5,
+            camera_shake_roll: 0.08,
+            camera_trauma_decay: 1.5,
+            camera_hard_landing_speed: 30.,
+            trackside_lead: 80.,
+            trackside_offset: 12.,
+        }
+    }
+}
+
+#[derive(Default)]
+struct GameTuningLoader;
+
+impl AssetLoader for GameTuningLoader {
+    type Asset = GameTuning;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<GameTuning, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tuning.ron"]
+    }
+}
+
+#[derive(Resource)]
+struct GameTuningHandle(Handle<GameTuning>);
+
+fn load_tuning(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(GameTuningHandle(
+        asset_server.load(TUNING_PATH),
+    ));
+}
+
+fn apply_tuning(
+    mut events: EventReader<AssetEvent<GameTuning>>,
+    handle: Res<GameTuningHandle>,
+    assets: Res<Assets<GameTuning>>,
+    mut tuning: ResMut<GameTuning>,
+) {
+    for event in events.read() {
+        if !event.is_loaded_with_dependencies(&handle.0)
+            && !event.is_modified(&handle.0)
+        {
+            continue;
+        }
+        if let Some(loaded) = assets.get(&handle.0) {
+            info!("applying game tuning");
+            *tuning = loaded.clone();
+        }
+    }
+}
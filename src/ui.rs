@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+pub const FONT_PATH: &str =
+    "fonts/Alfa_Slab_One/AlfaSlabOne-Regular.ttf";
+
+/// Marks buttons that should react to hover/press
+/// with the main menu colors.
+#[derive(Component)]
+pub struct MainMenuButton;
+
+pub fn main_menu_text_button(
+    text: &str,
+    font: Handle<Font>,
+) -> impl Bundle {
+    (
+        Name::new(format!("{text} Button")),
+        Button,
+        MainMenuButton,
+        Node {
+            padding: UiRect::axes(
+                Val::Px(15.),
+                Val::Px(5.),
+            ),
+            ..default()
+        },
+        children![(
+            Text::new(text),
+            TextFont { font, ..default() }
+        )],
+    )
+}
@@ -0,0 +1,111 @@
+//! Keys held on the keyboard have to reach the
+//! rider's [`ControlInput`] through the input
+//! contexts, which the autopilot playtests skip.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use landing::{
+    autopilot::Autopilot,
+    headless::playtest_app,
+    movement::{ControlInput, GroundState},
+    playing::Player,
+    tuning::GameTuning,
+};
+
+const SEED: u32 = 12345;
+
+fn keyboard_app() -> App {
+    let mut app = playtest_app(
+        SEED,
+        GameTuning::default(),
+        Duration::from_secs_f64(1. / 64.),
+    );
+    app.world_mut().remove_resource::<Autopilot>();
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn press(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .press(key);
+}
+
+fn release(app: &mut App, key: KeyCode) {
+    app.world_mut()
+        .resource_mut::<ButtonInput<KeyCode>>()
+        .release(key);
+}
+
+fn player(app: &mut App) -> (ControlInput, GroundState) {
+    let mut players = app
+        .world_mut()
+        .query_filtered::<(&ControlInput, &GroundState), With<Player>>();
+    let (input, state) =
+        players.single(app.world()).unwrap();
+    (*input, *state)
+}
+
+#[test]
+fn held_keys_steer_the_rider() {
+    let mut app = keyboard_app();
+    for _ in 0..8 {
+        app.update();
+    }
+    let (input, _) = player(&mut app);
+    assert_eq!(input.steer, Vec2::ZERO);
+
+    press(&mut app, KeyCode::KeyD);
+    for _ in 0..16 {
+        app.update();
+    }
+    let (input, _) = player(&mut app);
+    assert!(input.steer.x > 0., "{input:?}");
+
+    release(&mut app, KeyCode::KeyD);
+    press(&mut app, KeyCode::KeyA);
+    for _ in 0..16 {
+        app.update();
+    }
+    let (input, _) = player(&mut app);
+    assert!(input.steer.x < 0., "{input:?}");
+}
+
+#[test]
+fn jumping_switches_to_the_airborne_context() {
+    let mut app = keyboard_app();
+    // settle onto the snow first
+    for _ in 0..32 {
+        app.update();
+    }
+
+    // long enough to fully charge, so the jump
+    // outlasts the grounded context
+    press(&mut app, KeyCode::Space);
+    for _ in 0..48 {
+        app.update();
+    }
+    let (input, state) = player(&mut app);
+    assert!(input.jump, "{input:?}");
+    assert!(state.is_grounded(), "{state:?}");
+
+    release(&mut app, KeyCode::Space);
+    let took_off = (0..16).any(|_| {
+        app.update();
+        !player(&mut app).1.is_grounded()
+    });
+    assert!(took_off);
+    assert!(!player(&mut app).0.jump);
+
+    // the same key fast falls once the airborne
+    // context takes over
+    press(&mut app, KeyCode::Space);
+    let fast_fell = (0..32).any(|_| {
+        app.update();
+        let (input, state) = player(&mut app);
+        !state.is_grounded() && input.fast_fall
+    });
+    assert!(fast_fell);
+}
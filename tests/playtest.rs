@@ -0,0 +1,61 @@
+//! The autopilot should get down the first few
+//! seeds without falling through the terrain,
+//! crashing out or missing every landing.
+
+use std::time::Duration;
+
+use landing::{
+    headless::{PlaytestReport, playtest},
+    scoring::LandingQuality,
+    tuning::GameTuning,
+};
+
+const SEEDS: [u32; 3] = [12345, 12346, 12347];
+const SECONDS: f32 = 30.;
+
+fn ride(seed: u32) -> PlaytestReport {
+    playtest(
+        seed,
+        SECONDS,
+        GameTuning::default(),
+        Duration::from_secs_f64(1. / 64.),
+    )
+}
+
+#[test]
+fn autopilot_rides_the_first_seeds() {
+    for seed in SEEDS {
+        let report = ride(seed);
+        assert!(!report.fell_through, "{report:?}");
+        assert!(!report.game_over, "{report:?}");
+        assert!(report.seconds >= SECONDS, "{report:?}");
+        assert!(report.distance > 400., "{report:?}");
+        assert!(report.hits <= 2, "{report:?}");
+
+        let landings = LandingQuality::ALL
+            .map(|quality| report.landings.count(quality));
+        assert!(
+            landings.iter().sum::<u32>() >= 2,
+            "{report:?}"
+        );
+        // not every landing a flop
+        assert!(
+            landings[..3].iter().sum::<u32>() > 0,
+            "{report:?}"
+        );
+    }
+}
+
+#[test]
+fn the_same_seed_plays_out_the_same() {
+    let first = ride(SEEDS[0]);
+    let second = ride(SEEDS[0]);
+    assert_eq!(first.distance, second.distance);
+    assert_eq!(first.hits, second.hits);
+    for quality in LandingQuality::ALL {
+        assert_eq!(
+            first.landings.count(quality),
+            second.landings.count(quality)
+        );
+    }
+}
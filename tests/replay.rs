@@ -0,0 +1,83 @@
+//! A recorded run has to replay to exactly the
+//! same place, or replays and ghosts drift.
+
+use std::time::Duration;
+
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use landing::{
+    autopilot::Autopilot,
+    headless::playtest_app,
+    playing::Player,
+    replay::{
+        InputRecorder, Replay, ReplayPlayback, ReplayPlugin,
+    },
+    tuning::GameTuning,
+};
+
+const SEED: u32 = 12345;
+const TICKS: usize = 600;
+
+fn timestep() -> Duration {
+    Duration::from_secs_f64(1. / 64.)
+}
+
+fn final_position(app: &mut App) -> Vec3 {
+    let mut players = app
+        .world_mut()
+        .query_filtered::<&Position, With<Player>>();
+    players.single(app.world()).unwrap().0
+}
+
+#[test]
+fn recorded_run_replays_to_the_same_position() {
+    // the autopilot rides while the recorder
+    // listens
+    let mut recording = playtest_app(
+        SEED,
+        GameTuning::default(),
+        timestep(),
+    );
+    recording.add_plugins(ReplayPlugin);
+    recording.finish();
+    recording.cleanup();
+    for _ in 0..TICKS {
+        recording.update();
+    }
+    let recorded_position = final_position(&mut recording);
+    // actually went somewhere
+    assert!(recorded_position.z < -100.);
+    let replay = recording
+        .world()
+        .resource::<InputRecorder>()
+        .0
+        .clone();
+    assert_eq!(replay.seed, SEED);
+    assert!(!replay.ticks.is_empty());
+
+    // through the file format and back
+    let replay =
+        Replay::from_ron(&replay.to_ron().unwrap())
+            .unwrap();
+
+    let mut playback = playtest_app(
+        SEED,
+        GameTuning::default(),
+        timestep(),
+    );
+    playback
+        .add_plugins(ReplayPlugin)
+        .insert_resource(ReplayPlayback { replay, tick: 0 })
+        .world_mut()
+        .remove_resource::<Autopilot>();
+    playback.finish();
+    playback.cleanup();
+    for _ in 0..TICKS {
+        playback.update();
+    }
+
+    assert_eq!(
+        final_position(&mut playback),
+        recorded_position
+    );
+}